@@ -0,0 +1,472 @@
+use std::fmt::{Display, Formatter};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Where submission attempts are logged, relative to the repo root
+pub const DEFAULT_LOG_PATH: &str = "answers/attempts.txt";
+
+/// How long (in seconds) the site makes you wait after a wrong answer, at least; after a few of
+/// them it says to wait longer, which gets logged as its own Wait verdict
+pub const REJECTION_COOLDOWN: u64 = 60;
+
+/// One half of one day, e.g. "12b"
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Puzzle {
+    pub day: u32,
+    // 1 for part a, 2 for part b; this is what the site calls it
+    pub level: u32,
+}
+
+impl FromStr for Puzzle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("Expected a day like 12b, got {:?}", s);
+
+        let level = match s.chars().last() {
+            Some('a') => 1,
+            Some('b') => 2,
+            _ => return Err(err()),
+        };
+
+        let day = s[..s.len() - 1].parse::<u32>().map_err(|_| err())?;
+
+        if !(1..=25).contains(&day) {
+            return Err(err());
+        }
+
+        Ok(Puzzle { day, level })
+    }
+}
+
+impl Display for Puzzle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let part = if self.level == 1 { 'a' } else { 'b' };
+        write!(f, "{}{}", self.day, part)
+    }
+}
+
+/// What the site said about a submitted answer
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    // wrong, but the site didn't say which way
+    Wrong,
+    // rate limited; the answer was not checked, try again after this many seconds
+    Wait(u64),
+    // the puzzle was already solved, or isn't unlocked yet; the answer was not checked
+    WrongLevel,
+}
+
+impl Verdict {
+    /// Whether this verdict means the answer is definitely not the right one
+    pub fn is_rejection(&self) -> bool {
+        matches!(self, Verdict::TooHigh | Verdict::TooLow | Verdict::Wrong)
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct"),
+            Verdict::TooHigh => write!(f, "too_high"),
+            Verdict::TooLow => write!(f, "too_low"),
+            Verdict::Wrong => write!(f, "wrong"),
+            Verdict::Wait(secs) => write!(f, "wait:{}", secs),
+            Verdict::WrongLevel => write!(f, "wrong_level"),
+        }
+    }
+}
+
+impl FromStr for Verdict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "correct" => Ok(Verdict::Correct),
+            "too_high" => Ok(Verdict::TooHigh),
+            "too_low" => Ok(Verdict::TooLow),
+            "wrong" => Ok(Verdict::Wrong),
+            "wrong_level" => Ok(Verdict::WrongLevel),
+            other => match other.strip_prefix("wait:") {
+                Some(secs) => secs
+                    .parse::<u64>()
+                    .map(Verdict::Wait)
+                    .map_err(|_| format!("Bad wait time in verdict {:?}", other)),
+                None => Err(format!("Unknown verdict {:?}", other)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Attempt {
+    // seconds since the unix epoch
+    pub timestamp: u64,
+    pub puzzle: Puzzle,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+impl Attempt {
+    /// Whether the answer can go in the log; it has to fit in one tab-separated field, so it
+    /// can't be empty or have whitespace or control characters in it
+    pub fn check_answer(answer: &str) -> Result<(), String> {
+        if answer.is_empty() {
+            return Err("Can't use an empty answer".to_string());
+        }
+        if answer.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(format!(
+                "Can't use {:?} as an answer; it has whitespace or control characters in it",
+                answer
+            ));
+        }
+        Ok(())
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.timestamp, self.puzzle, self.answer, self.verdict
+        )
+    }
+
+    fn from_line(line: &str) -> Result<Attempt, String> {
+        let tokens: Vec<&str> = line.split('\t').collect();
+        if tokens.len() != 4 {
+            return Err(format!("Expected 4 tab-separated fields, got {:?}", line));
+        }
+
+        let timestamp = tokens[0]
+            .parse::<u64>()
+            .map_err(|_| format!("Bad timestamp in {:?}", line))?;
+
+        Ok(Attempt {
+            timestamp,
+            puzzle: tokens[1].parse()?,
+            answer: tokens[2].to_string(),
+            verdict: tokens[3].parse()?,
+        })
+    }
+}
+
+/// Every answer we've submitted, and what the site said about it. Stored as a tab-separated
+/// text file, one attempt per line, appended to as we go.
+#[derive(Debug)]
+pub struct AttemptLog {
+    path: PathBuf,
+    attempts: Vec<Attempt>,
+}
+
+impl AttemptLog {
+    /// Loads the log at the given path; a missing file is just an empty log
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<AttemptLog, String> {
+        let path = path.as_ref().to_path_buf();
+
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Could not read {}: {}", path.display(), e)),
+        };
+
+        let attempts = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Attempt::from_line)
+            .collect::<Result<Vec<Attempt>, String>>()?;
+
+        Ok(AttemptLog { path, attempts })
+    }
+
+    /// Adds the attempt to the log, both in memory and on disk
+    pub fn record(&mut self, attempt: Attempt) -> Result<(), String> {
+        Attempt::check_answer(&attempt.answer)?;
+
+        let io_err = |e: std::io::Error| format!("Could not write {}: {}", self.path.display(), e);
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(io_err)?;
+        writeln!(file, "{}", attempt.to_line()).map_err(io_err)?;
+
+        self.attempts.push(attempt);
+        Ok(())
+    }
+
+    pub fn for_puzzle(&self, puzzle: Puzzle) -> impl Iterator<Item = &Attempt> {
+        self.attempts.iter().filter(move |a| a.puzzle == puzzle)
+    }
+
+    /// The accepted answer for this puzzle, if we've found it
+    pub fn correct_answer(&self, puzzle: Puzzle) -> Option<&str> {
+        self.for_puzzle(puzzle)
+            .find(|a| a.verdict == Verdict::Correct)
+            .map(|a| a.answer.as_str())
+    }
+
+    /// The attempt where this exact answer was rejected, if it ever was
+    pub fn rejection_of(&self, puzzle: Puzzle, answer: &str) -> Option<&Attempt> {
+        self.for_puzzle(puzzle)
+            .find(|a| a.answer == answer && a.verdict.is_rejection())
+    }
+
+    /// The earliest time (in unix seconds) the site will accept another answer for this puzzle,
+    /// based on the most recent response we got: either it said to wait, or it rejected the
+    /// answer, which always comes with a cooldown
+    pub fn not_before(&self, puzzle: Puzzle) -> Option<u64> {
+        self.for_puzzle(puzzle)
+            .last()
+            .and_then(|a| match a.verdict {
+                Verdict::Wait(secs) => Some(a.timestamp + secs),
+                verdict if verdict.is_rejection() => Some(a.timestamp + REJECTION_COOLDOWN),
+                _ => None,
+            })
+    }
+
+    /// The tightest range the answer can be in, based on too-high / too-low verdicts so far
+    pub fn bounds(&self, puzzle: Puzzle) -> Bounds {
+        let mut bounds = Bounds::default();
+
+        for attempt in self.for_puzzle(puzzle) {
+            let val = match attempt.answer.parse::<i128>() {
+                Ok(val) => val,
+                Err(_) => continue,
+            };
+
+            match attempt.verdict {
+                Verdict::TooHigh => {
+                    bounds.too_high = Some(bounds.too_high.map_or(val, |old| old.min(val)));
+                }
+                Verdict::TooLow => {
+                    bounds.too_low = Some(bounds.too_low.map_or(val, |old| old.max(val)));
+                }
+                _ => {}
+            }
+        }
+
+        bounds
+    }
+
+    /// Reasons to believe this answer is wrong, based on what the site has said so far;
+    /// empty if there's nothing against it
+    pub fn warnings(&self, puzzle: Puzzle, answer: &str) -> Vec<String> {
+        let mut out = Vec::new();
+
+        if let Some(correct) = self.correct_answer(puzzle) {
+            if correct != answer {
+                out.push(format!("{} was already solved with {}", puzzle, correct));
+            }
+        }
+
+        if let Some(rejected) = self.rejection_of(puzzle, answer) {
+            out.push(format!(
+                "{} was already rejected for {} ({})",
+                answer, puzzle, rejected.verdict
+            ));
+        } else if !self.bounds(puzzle).allows(answer) {
+            out.push(format!(
+                "{} is outside the known bounds for {}: {}",
+                answer,
+                puzzle,
+                self.bounds(puzzle)
+            ));
+        }
+
+        out
+    }
+}
+
+/// Exclusive bounds on a numeric answer; None means unbounded on that side
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Bounds {
+    // the biggest answer we were told was too low
+    pub too_low: Option<i128>,
+    // the smallest answer we were told was too high
+    pub too_high: Option<i128>,
+}
+
+impl Bounds {
+    /// Whether the answer could be right; non-numeric answers are never ruled out
+    pub fn allows(&self, answer: &str) -> bool {
+        let val = match answer.parse::<i128>() {
+            Ok(val) => val,
+            Err(_) => return true,
+        };
+
+        self.too_low.is_none_or(|low| val > low) && self.too_high.is_none_or(|high| val < high)
+    }
+}
+
+impl Display for Bounds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.too_low {
+            Some(low) => write!(f, "({}", low)?,
+            None => write!(f, "(-inf")?,
+        }
+        match self.too_high {
+            Some(high) => write!(f, ", {})", high),
+            None => write!(f, ", inf)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("aoc_2022_attempts_{}", std::process::id()))
+            .join(name);
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn parse_puzzle() {
+        assert_eq!("12b".parse::<Puzzle>(), Ok(Puzzle { day: 12, level: 2 }));
+        assert_eq!("1a".parse::<Puzzle>(), Ok(Puzzle { day: 1, level: 1 }));
+        assert!("26a".parse::<Puzzle>().is_err());
+        assert!("12".parse::<Puzzle>().is_err());
+        assert!("b".parse::<Puzzle>().is_err());
+
+        assert_eq!(Puzzle { day: 7, level: 2 }.to_string(), "7b");
+    }
+
+    #[test]
+    fn verdict_round_trip() {
+        for verdict in [
+            Verdict::Correct,
+            Verdict::TooHigh,
+            Verdict::TooLow,
+            Verdict::Wrong,
+            Verdict::Wait(42),
+            Verdict::WrongLevel,
+        ] {
+            assert_eq!(verdict.to_string().parse::<Verdict>(), Ok(verdict));
+        }
+    }
+
+    #[test]
+    fn record_and_reload() {
+        let path = temp_log_path("record_and_reload.txt");
+        let puzzle = Puzzle { day: 3, level: 1 };
+
+        let mut log = AttemptLog::load(&path).unwrap();
+        assert_eq!(log.for_puzzle(puzzle).count(), 0);
+        assert_eq!(log.not_before(puzzle), None);
+
+        log.record(Attempt {
+            timestamp: 100,
+            puzzle,
+            answer: "123".to_string(),
+            verdict: Verdict::TooLow,
+        })
+        .unwrap();
+        // wrong answers come with a cooldown
+        assert_eq!(log.not_before(puzzle), Some(160));
+        log.record(Attempt {
+            timestamp: 200,
+            puzzle,
+            answer: "456".to_string(),
+            verdict: Verdict::Wait(30),
+        })
+        .unwrap();
+
+        let log = AttemptLog::load(&path).unwrap();
+        assert_eq!(log.for_puzzle(puzzle).count(), 2);
+        assert!(log.rejection_of(puzzle, "123").is_some());
+        assert!(log.rejection_of(puzzle, "456").is_none());
+        assert_eq!(log.correct_answer(puzzle), None);
+        assert_eq!(log.not_before(puzzle), Some(230));
+        assert_eq!(log.for_puzzle(Puzzle { day: 3, level: 2 }).count(), 0);
+    }
+
+    #[test]
+    fn unloggable_answers() {
+        let path = temp_log_path("unloggable_answers.txt");
+        let puzzle = Puzzle { day: 10, level: 2 };
+
+        let mut log = AttemptLog::load(&path).unwrap();
+        log.record(Attempt {
+            timestamp: 100,
+            puzzle,
+            answer: "RFKZCPEF".to_string(),
+            verdict: Verdict::Wrong,
+        })
+        .unwrap();
+
+        // e.g. a picture, when the letters couldn't be read
+        for answer in ["\n###..#\n#..#.#", "a\tb", "two words", ""] {
+            let err = log
+                .record(Attempt {
+                    timestamp: 200,
+                    puzzle,
+                    answer: answer.to_string(),
+                    verdict: Verdict::Wrong,
+                })
+                .unwrap_err();
+            assert!(err.starts_with("Can't use"), "{}", err);
+        }
+
+        // nothing bad got written, so the log still loads
+        let log = AttemptLog::load(&path).unwrap();
+        assert_eq!(log.for_puzzle(puzzle).count(), 1);
+        assert!(log.rejection_of(puzzle, "RFKZCPEF").is_some());
+    }
+
+    #[test]
+    fn bounds_and_warnings() {
+        let path = temp_log_path("bounds_and_warnings.txt");
+        let puzzle = Puzzle { day: 9, level: 2 };
+
+        let mut log = AttemptLog::load(&path).unwrap();
+        assert_eq!(log.bounds(puzzle), Bounds::default());
+        assert!(log.warnings(puzzle, "5000").is_empty());
+
+        for (answer, verdict) in [
+            ("1000", Verdict::TooLow),
+            ("9000", Verdict::TooHigh),
+            ("2000", Verdict::TooLow),
+            ("7000", Verdict::TooHigh),
+            ("4000", Verdict::Wrong),
+        ] {
+            log.record(Attempt {
+                timestamp: 0,
+                puzzle,
+                answer: answer.to_string(),
+                verdict,
+            })
+            .unwrap();
+        }
+
+        let bounds = log.bounds(puzzle);
+        assert_eq!(
+            bounds,
+            Bounds {
+                too_low: Some(2000),
+                too_high: Some(7000)
+            }
+        );
+        assert_eq!(bounds.to_string(), "(2000, 7000)");
+        assert!(bounds.allows("2001"));
+        assert!(!bounds.allows("2000"));
+        assert!(!bounds.allows("7000"));
+        assert!(bounds.allows("PLPAFBCL"));
+
+        assert!(log.warnings(puzzle, "5000").is_empty());
+        assert_eq!(log.warnings(puzzle, "8000").len(), 1);
+        assert_eq!(log.warnings(puzzle, "4000").len(), 1);
+        assert!(log.warnings(Puzzle { day: 9, level: 1 }, "8000").is_empty());
+    }
+}
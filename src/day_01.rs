@@ -1,9 +1,16 @@
-fn input() -> String {
-    std::fs::read_to_string("input/input_01.txt").expect("Should be able to read the file")
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io::BufRead;
+
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_01.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
 
     let val = a_with_input(&contents);
 
@@ -11,63 +18,161 @@ pub fn a() -> String {
 }
 
 fn a_with_input(input: &str) -> u32 {
-    let mut max_elf_amt = u32::MIN;
-    let mut current_elf_amt = 0;
+    top_n_groups(input.as_bytes(), 1)
+        .expect("Should be able to read the input")
+        .first()
+        .map(|elf| elf.total)
+        .unwrap_or(u32::MIN)
+}
+
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
+
+    let num_elves = params.get("elves", 3);
+
+    let val = b_with_input(&contents, num_elves);
+
+    val.to_string()
+}
+
+fn b_with_input(input: &str, num_elves: usize) -> u32 {
+    top_n_groups(input.as_bytes(), num_elves)
+        .expect("Should be able to read the input")
+        .into_iter()
+        .map(|elf| elf.total)
+        .sum()
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct GroupTotal {
+    // 0-indexed position of the group in the input
+    index: usize,
+    total: u32,
+}
 
-    for line in input.lines() {
-        if line.is_empty() {
-            if current_elf_amt > max_elf_amt {
-                max_elf_amt = current_elf_amt;
+/// Finds the n blank-line-separated groups with the largest totals, biggest first (ties go to the
+/// earlier group). Reads the input a line at a time, and only ever holds on to n groups.
+fn top_n_groups<R: BufRead>(mut input: R, n: usize) -> std::io::Result<Vec<GroupTotal>> {
+    // min-heap of the best groups so far, so the worst one is cheap to kick out; among equal
+    // totals the later group counts as smaller, so it's the one that goes
+    let mut best: BinaryHeap<Reverse<(u32, Reverse<usize>)>> = BinaryHeap::with_capacity(n + 1);
+
+    let mut offer = |index: usize, total: u32| {
+        best.push(Reverse((total, Reverse(index))));
+        if best.len() > n {
+            best.pop();
+        }
+    };
+
+    let mut line = String::new();
+    let mut index = 0;
+    // None if we're between groups
+    let mut running: Option<u32> = None;
+
+    loop {
+        line.clear();
+        let bytes_read = input.read_line(&mut line)?;
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            if let Some(total) = running.take() {
+                offer(index, total);
+                index += 1;
+            }
+
+            if bytes_read == 0 {
+                break;
             }
-            current_elf_amt = 0;
             continue;
         }
 
-        let amt = line.parse::<u32>().expect("Line should be empty or an int");
-        current_elf_amt += amt;
+        let amt = trimmed.parse::<u32>().map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Line should be empty or an int, got {:?}: {}", trimmed, e),
+            )
+        })?;
+        running = Some(running.unwrap_or(0) + amt);
     }
 
-    if current_elf_amt > max_elf_amt {
-        max_elf_amt = current_elf_amt;
-    }
-
-    max_elf_amt
+    Ok(best
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((total, Reverse(index)))| GroupTotal { index, total })
+        .collect())
 }
 
-pub fn b() -> String {
-    let contents = input();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let val = b_with_input(&contents);
+    const SAMPLE_INPUT_STR: &str = "1000
+2000
+3000
 
-    val.to_string()
-}
+4000
 
-fn b_with_input(input: &str) -> u32 {
-    let mut max_elves = Vec::with_capacity(4);
+5000
+6000
 
-    let mut current_elf_amt = 0;
+7000
+8000
+9000
 
-    for line in input.lines() {
-        if line.is_empty() {
-            max_elves.push(current_elf_amt);
-            if max_elves.len() > 3 {
-                max_elves.sort_by(|a, b| b.cmp(a));
-                max_elves.pop();
-            }
+10000";
 
-            current_elf_amt = 0;
-            continue;
-        }
+    #[test]
+    fn sample_a() {
+        assert_eq!(a_with_input(SAMPLE_INPUT_STR), 24000);
+    }
 
-        let amt = line.parse::<u32>().expect("Line should be empty or an int");
-        current_elf_amt += amt;
+    #[test]
+    fn sample_b() {
+        assert_eq!(b_with_input(SAMPLE_INPUT_STR, 3), 45000);
     }
 
-    max_elves.push(current_elf_amt);
-    if max_elves.len() > 3 {
-        max_elves.sort_by(|a, b| b.cmp(a));
-        max_elves.pop();
+    #[test]
+    fn ranked_groups() {
+        let actual = top_n_groups(SAMPLE_INPUT_STR.as_bytes(), 3).unwrap();
+        assert_eq!(
+            actual,
+            vec![
+                GroupTotal {
+                    index: 3,
+                    total: 24000
+                },
+                GroupTotal {
+                    index: 2,
+                    total: 11000
+                },
+                GroupTotal {
+                    index: 4,
+                    total: 10000
+                },
+            ]
+        );
+
+        // asking for more groups than there are just gives all of them
+        assert_eq!(
+            top_n_groups(SAMPLE_INPUT_STR.as_bytes(), 10).unwrap().len(),
+            5
+        );
+        assert!(top_n_groups(SAMPLE_INPUT_STR.as_bytes(), 0)
+            .unwrap()
+            .is_empty());
     }
 
-    max_elves.into_iter().sum()
+    #[test]
+    fn ties_and_odd_spacing() {
+        let input = "\n\n5\r\n\r\n\r\n2\n3\n\n1\n";
+        assert_eq!(
+            top_n_groups(input.as_bytes(), 2).unwrap(),
+            vec![
+                GroupTotal { index: 0, total: 5 },
+                GroupTotal { index: 1, total: 5 },
+            ]
+        );
+
+        assert!(top_n_groups("12\nbanana\n".as_bytes(), 1).is_err());
+    }
 }
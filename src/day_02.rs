@@ -1,9 +1,20 @@
-fn input() -> String {
-    std::fs::read_to_string("input/input_02.txt").expect("Should be able to read the file")
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_02.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
+
+    if params.get("optimize", false) {
+        return optimize_report(&contents);
+    }
 
     let val = a_with_input(&contents);
 
@@ -11,183 +22,245 @@ pub fn a() -> String {
 }
 
 fn a_with_input(input: &str) -> i32 {
-    enum Outcome {
-        WIN,
-        LOSS,
-        TIE,
-    }
-
-    impl Outcome {
-        fn reverse(&self) -> Outcome {
-            match self {
-                Outcome::WIN => Outcome::LOSS,
-                Outcome::LOSS => Outcome::WIN,
-                Outcome::TIE => Outcome::TIE,
-            }
-        }
-    }
+    Rules::rock_paper_scissors(ColumnMeaning::Moves(column_moves(&["X", "Y", "Z"])))
+        .total_score(input)
+}
 
-    // returns the result of a play from left to right
-    // note this is the outcome for the LEFT player
-    fn outcome(left: &str, right: &str) -> Outcome {
-        match left {
-            "A" => match right {
-                "X" => Outcome::TIE,
-                "Y" => Outcome::LOSS,
-                "Z" => Outcome::WIN,
-                _ => unimplemented!(),
-            },
-            "B" => match right {
-                "X" => Outcome::WIN,
-                "Y" => Outcome::TIE,
-                "Z" => Outcome::LOSS,
-                _ => unimplemented!(),
-            },
-            "C" => match right {
-                "X" => Outcome::LOSS,
-                "Y" => Outcome::WIN,
-                "Z" => Outcome::TIE,
-                _ => unimplemented!(),
-            },
-            _ => unimplemented!(),
-        }
-    }
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
 
-    let mut score = 0;
-    for line in input.lines() {
-        if line.is_empty() {
-            continue;
-        }
+    let val = b_with_input(&contents);
 
-        let mut tokens = line.split_whitespace();
-        let left = tokens.next().unwrap();
-        let right = tokens.next().unwrap();
+    val.to_string()
+}
 
-        if tokens.next().is_some() {
-            panic!()
-        }
+fn b_with_input(input: &str) -> i32 {
+    Rules::rock_paper_scissors(ColumnMeaning::Outcomes(column_outcomes(&["X", "Y", "Z"])))
+        .total_score(input)
+}
 
-        score += match outcome(left, right).reverse() {
-            Outcome::WIN => 6,
-            Outcome::LOSS => 0,
-            Outcome::TIE => 3,
-        };
+// The guide never actually says what its second column means, so try every reading: each way of
+// assigning X/Y/Z to rock/paper/scissors, plus the part B reading where they're loss/draw/win.
+fn optimize_report(input: &str) -> String {
+    let scores = score_mappings(input);
 
-        score += match right {
-            "X" => 1,
-            "Y" => 2,
-            "Z" => 3,
-            _ => panic!(),
-        };
+    let mut out = String::new();
+    for mapping in &scores {
+        out.push_str(&format!("{}: {}\n", mapping.description, mapping.score));
     }
 
-    score
-}
+    // max_by_key keeps the last of equal maxima, so go backwards to prefer the first one
+    let best = scores
+        .iter()
+        .rev()
+        .max_by_key(|mapping| mapping.score)
+        .unwrap();
+    out.push_str(&format!("Best: {} ({})", best.description, best.score));
 
-pub fn b() -> String {
-    let contents = input();
+    out
+}
 
-    let val = b_with_input(&contents);
+const RPS_MOVE_NAMES: [&str; 3] = ["rock", "paper", "scissors"];
+const GUIDE_COLUMN: [&str; 3] = ["X", "Y", "Z"];
 
-    val.to_string()
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct MappingScore {
+    // e.g. "X=rock Y=paper Z=scissors"
+    description: String,
+    score: i32,
 }
 
-fn b_with_input(input: &str) -> i32 {
-    enum Outcome {
-        WIN,
-        LOSS,
-        TIE,
+fn score_mappings(input: &str) -> Vec<MappingScore> {
+    let mut candidates: Vec<(String, ColumnMeaning)> = Vec::new();
+
+    for moves in (0..RPS_MOVE_NAMES.len()).permutations(RPS_MOVE_NAMES.len()) {
+        let description = GUIDE_COLUMN
+            .iter()
+            .zip(moves.iter())
+            .map(|(tok, &m)| format!("{}={}", tok, RPS_MOVE_NAMES[m]))
+            .join(" ");
+        let meaning = ColumnMeaning::Moves(
+            GUIDE_COLUMN
+                .iter()
+                .zip(moves)
+                .map(|(tok, m)| (tok.to_string(), m))
+                .collect(),
+        );
+        candidates.push((description, meaning));
     }
 
-    enum Play {
-        ROCK,
-        SCISSORS,
-        PAPER,
-    }
+    candidates.push((
+        "X=lose Y=draw Z=win".to_string(),
+        ColumnMeaning::Outcomes(column_outcomes(&GUIDE_COLUMN)),
+    ));
+
+    candidates
+        .into_iter()
+        .map(|(description, meaning)| MappingScore {
+            description,
+            score: Rules::rock_paper_scissors(meaning).total_score(input),
+        })
+        .collect()
+}
 
-    fn outcome_from_token(token: &str) -> Outcome {
-        match token {
-            "X" => Outcome::LOSS,
-            "Y" => Outcome::TIE,
-            "Z" => Outcome::WIN,
-            _ => unimplemented!(),
-        }
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+// A game where the moves are arranged in a circle, and each move beats the moves an odd number of
+// steps behind it (and loses to the ones an even number of steps behind). With three moves in the
+// order rock, paper, scissors this is the usual game; with five in the order rock, paper,
+// scissors, Spock, lizard it's rock-paper-scissors-lizard-Spock. There have to be an odd number
+// of moves, or some pairs would be neither a win nor a loss.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct CyclicGame {
+    num_moves: usize,
+}
+
+impl CyclicGame {
+    fn new(num_moves: usize) -> CyclicGame {
+        assert!(
+            num_moves % 2 == 1,
+            "Cyclic games need an odd number of moves"
+        );
+        CyclicGame { num_moves }
     }
 
-    impl Outcome {
-        fn score(&self) -> i32 {
-            match self {
-                Outcome::LOSS => 0,
-                Outcome::TIE => 3,
-                Outcome::WIN => 6,
-            }
+    // the outcome for the player making `mine`
+    fn outcome(&self, mine: usize, theirs: usize) -> Outcome {
+        let steps = (mine + self.num_moves - theirs) % self.num_moves;
+        if steps == 0 {
+            Outcome::Draw
+        } else if steps % 2 == 1 {
+            Outcome::Win
+        } else {
+            Outcome::Loss
         }
     }
 
-    impl Play {
-        fn score(&self) -> i32 {
-            match self {
-                Play::ROCK => 1,
-                Play::PAPER => 2,
-                Play::SCISSORS => 3,
-            }
+    // a move which gets the desired outcome against `theirs`; when there's more than one, this is
+    // the nearest one around the circle
+    fn move_for(&self, theirs: usize, desired: Outcome) -> usize {
+        match desired {
+            Outcome::Draw => theirs,
+            Outcome::Win => (theirs + 1) % self.num_moves,
+            Outcome::Loss => (theirs + self.num_moves - 1) % self.num_moves,
         }
     }
+}
 
-    fn play_from_token(token: &str) -> Play {
-        match token {
-            "A" => Play::ROCK,
-            "B" => Play::PAPER,
-            "C" => Play::SCISSORS,
-            _ => panic!(),
-        }
+// Points per round: something for the move you made, plus something for how it turned out
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Scoring {
+    move_scores: Vec<i32>,
+    outcome_scores: HashMap<Outcome, i32>,
+}
+
+impl Scoring {
+    fn round_score(&self, mine: usize, outcome: Outcome) -> i32 {
+        self.move_scores[mine] + self.outcome_scores[&outcome]
     }
+}
 
-    fn desired_play(left: Play, desired_outcome: Outcome) -> Play {
-        match left {
-            Play::ROCK => match desired_outcome {
-                Outcome::WIN => Play::PAPER,
-                Outcome::LOSS => Play::SCISSORS,
-                Outcome::TIE => Play::ROCK,
-            },
-            Play::SCISSORS => match desired_outcome {
-                Outcome::WIN => Play::ROCK,
-                Outcome::LOSS => Play::PAPER,
-                Outcome::TIE => Play::SCISSORS,
-            },
-            Play::PAPER => match desired_outcome {
-                Outcome::WIN => Play::SCISSORS,
-                Outcome::LOSS => Play::ROCK,
-                Outcome::TIE => Play::PAPER,
+// What the second column of the strategy guide is telling us to do
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum ColumnMeaning {
+    // play this move
+    Moves(HashMap<String, usize>),
+    // play whatever gets this outcome
+    Outcomes(HashMap<String, Outcome>),
+}
+
+fn column_moves(tokens: &[&str]) -> HashMap<String, usize> {
+    tokens
+        .iter()
+        .enumerate()
+        .map(|(idx, tok)| (tok.to_string(), idx))
+        .collect()
+}
+
+// tokens are for loss, draw, win in that order
+fn column_outcomes(tokens: &[&str]) -> HashMap<String, Outcome> {
+    assert_eq!(tokens.len(), 3);
+    tokens
+        .iter()
+        .zip([Outcome::Loss, Outcome::Draw, Outcome::Win])
+        .map(|(tok, outcome)| (tok.to_string(), outcome))
+        .collect()
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Rules {
+    game: CyclicGame,
+    // what the first column means; always a move
+    their_moves: HashMap<String, usize>,
+    our_column: ColumnMeaning,
+    scoring: Scoring,
+}
+
+impl Rules {
+    // A/B/C are rock/paper/scissors; 1/2/3 points for those, and 0/3/6 for a loss/draw/win
+    fn rock_paper_scissors(our_column: ColumnMeaning) -> Rules {
+        Rules {
+            game: CyclicGame::new(3),
+            their_moves: column_moves(&["A", "B", "C"]),
+            our_column,
+            scoring: Scoring {
+                move_scores: vec![1, 2, 3],
+                outcome_scores: [(Outcome::Loss, 0), (Outcome::Draw, 3), (Outcome::Win, 6)]
+                    .into_iter()
+                    .collect(),
             },
         }
     }
 
-    let mut score = 0;
-    for line in input.lines() {
-        if line.is_empty() {
-            continue;
+    // the move we make and the outcome we get, given one line of the guide
+    fn play_round(&self, left: &str, right: &str) -> (usize, Outcome) {
+        let theirs = *self
+            .their_moves
+            .get(left)
+            .unwrap_or_else(|| panic!("Unknown move {:?}", left));
+
+        match &self.our_column {
+            ColumnMeaning::Moves(moves) => {
+                let mine = *moves
+                    .get(right)
+                    .unwrap_or_else(|| panic!("Unknown move {:?}", right));
+                (mine, self.game.outcome(mine, theirs))
+            }
+            ColumnMeaning::Outcomes(outcomes) => {
+                let desired = *outcomes
+                    .get(right)
+                    .unwrap_or_else(|| panic!("Unknown outcome {:?}", right));
+                (self.game.move_for(theirs, desired), desired)
+            }
         }
+    }
 
-        let mut tokens = line.split_whitespace();
-        let left = tokens.next().unwrap();
-        let right = tokens.next().unwrap();
-
-        if tokens.next().is_some() {
-            panic!()
-        }
+    fn total_score(&self, input: &str) -> i32 {
+        let mut score = 0;
+        for line in input.lines() {
+            if line.is_empty() {
+                continue;
+            }
 
-        let left_play = play_from_token(left);
-        let desired_outcome = outcome_from_token(right);
+            let mut tokens = line.split_whitespace();
+            let left = tokens.next().unwrap();
+            let right = tokens.next().unwrap();
 
-        score += desired_outcome.score();
+            if tokens.next().is_some() {
+                panic!()
+            }
 
-        let desired_play = desired_play(left_play, desired_outcome);
+            let (mine, outcome) = self.play_round(left, right);
+            score += self.scoring.round_score(mine, outcome);
+        }
 
-        score += desired_play.score();
+        score
     }
-
-    score
 }
 
 #[cfg(test)]
@@ -236,4 +309,88 @@ C Z";
         assert_eq!(b_with_input("C Y"), 3 + 3); // tie -- 3 points; they play scissors, so we play scissors, get 3 points
         assert_eq!(b_with_input("C Z"), 6 + 1); // win -- 6 points; they play scissors, so we play rock, get 1 point
     }
+
+    #[test]
+    fn cyclic_outcomes() {
+        let rps = CyclicGame::new(3);
+        // rock, paper, scissors
+        assert_eq!(rps.outcome(1, 0), Outcome::Win);
+        assert_eq!(rps.outcome(0, 2), Outcome::Win);
+        assert_eq!(rps.outcome(0, 1), Outcome::Loss);
+        assert_eq!(rps.outcome(2, 2), Outcome::Draw);
+
+        // rock, paper, scissors, Spock, lizard
+        let rpsls = CyclicGame::new(5);
+        let (rock, paper, scissors, spock, lizard) = (0, 1, 2, 3, 4);
+        assert_eq!(rpsls.outcome(scissors, paper), Outcome::Win);
+        assert_eq!(rpsls.outcome(paper, rock), Outcome::Win);
+        assert_eq!(rpsls.outcome(rock, lizard), Outcome::Win);
+        assert_eq!(rpsls.outcome(lizard, spock), Outcome::Win);
+        assert_eq!(rpsls.outcome(spock, scissors), Outcome::Win);
+        assert_eq!(rpsls.outcome(scissors, lizard), Outcome::Win);
+        assert_eq!(rpsls.outcome(lizard, paper), Outcome::Win);
+        assert_eq!(rpsls.outcome(paper, spock), Outcome::Win);
+        assert_eq!(rpsls.outcome(spock, rock), Outcome::Win);
+        assert_eq!(rpsls.outcome(rock, scissors), Outcome::Win);
+        assert_eq!(rpsls.outcome(rock, paper), Outcome::Loss);
+
+        for theirs in 0..5 {
+            for desired in [Outcome::Loss, Outcome::Draw, Outcome::Win] {
+                assert_eq!(
+                    rpsls.outcome(rpsls.move_for(theirs, desired), theirs),
+                    desired
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rock_paper_scissors_lizard_spock() {
+        let rules = Rules {
+            game: CyclicGame::new(5),
+            their_moves: column_moves(&["A", "B", "C", "D", "E"]),
+            our_column: ColumnMeaning::Moves(column_moves(&["V", "W", "X", "Y", "Z"])),
+            scoring: Scoring {
+                move_scores: vec![1, 2, 3, 4, 5],
+                outcome_scores: [(Outcome::Loss, 0), (Outcome::Draw, 1), (Outcome::Win, 2)]
+                    .into_iter()
+                    .collect(),
+            },
+        };
+
+        // paper beats rock, lizard beats Spock, Spock loses to paper, draw with scissors
+        assert_eq!(
+            rules.total_score("A W\nD Z\nB Y\nC X"),
+            (2 + 2) + (5 + 2) + 4 + (3 + 1)
+        );
+    }
+
+    #[test]
+    fn optimize_sample() {
+        let input = "A Y
+B X
+C Z";
+        let scores = score_mappings(input);
+        assert_eq!(scores.len(), 7);
+
+        // the identity mapping and the outcome reading are parts A and B
+        assert_eq!(
+            scores[0],
+            MappingScore {
+                description: "X=rock Y=paper Z=scissors".to_string(),
+                score: 15
+            }
+        );
+        assert_eq!(
+            scores[6],
+            MappingScore {
+                description: "X=lose Y=draw Z=win".to_string(),
+                score: 12
+            }
+        );
+
+        let report = optimize_report(input);
+        // X=scissors Y=paper Z=rock wins all three rounds
+        assert!(report.ends_with("Best: X=scissors Y=paper Z=rock (24)"));
+    }
 }
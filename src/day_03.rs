@@ -1,11 +1,15 @@
-use std::collections::HashSet;
+use std::fmt::{Debug, Formatter};
+use std::ops::{BitAnd, BitOr};
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_03.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_03.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
 
     let val = a_with_input(&contents);
 
@@ -13,54 +17,138 @@ pub fn a() -> String {
 }
 
 fn a_with_input(input: &str) -> i32 {
-    input
-        .lines()
-        .map(|line| a_find_dupe(line))
-        .map(|dupe| priority(dupe))
-        .sum::<i32>()
+    input.lines().map(a_find_dupe).map(priority).sum::<i32>()
 }
 
 fn a_find_dupe(line: &str) -> char {
     // NOTE: input is ASCII so .len() does what we want
+    let line = line.trim();
     assert_eq!(line.len() % 2, 0);
 
-    let mut seen = HashSet::new();
-
-    let mut char_idx = 0;
-
-    for c in line.trim().chars() {
-        if char_idx * 2 < line.len() {
-            seen.insert(c);
-        } else if seen.contains(&c) {
-            return c;
-        }
+    let (left, right) = line.split_at(line.len() / 2);
+    let common = ItemSet::from_items(left) & ItemSet::from_items(right);
 
-        char_idx += 1;
-    }
-
-    unreachable!("Did not find a duplicate, possible bad input")
+    common
+        .single()
+        .expect("Did not find exactly one duplicate, possible bad input")
 }
 
 // gets the priority of a character.
 // unspecified behavior when c is not in [A-Za-z]
 fn priority(c: char) -> i32 {
-    if c >= 'a' && c <= 'z' {
+    if c.is_ascii_lowercase() {
         1 + (c as i32) - ('a' as i32)
     } else {
         27 + (c as i32) - ('A' as i32)
     }
 }
 
-pub fn b() -> String {
-    let contents = input();
+// inverse of priority; p should be in 1..=52
+fn item_with_priority(p: i32) -> char {
+    if p <= 26 {
+        (b'a' + (p - 1) as u8) as char
+    } else {
+        (b'A' + (p - 27) as u8) as char
+    }
+}
+
+// A set of items, with bit (priority - 1) set for each item in it. There are only 52 possible
+// items, so intersections and unions are a single AND / OR.
+#[derive(Copy, Clone, Eq, PartialEq, Default)]
+struct ItemSet(u64);
+
+impl ItemSet {
+    fn empty() -> ItemSet {
+        ItemSet(0)
+    }
+
+    // everything that could possibly be in a rucksack; the identity for intersection
+    fn all() -> ItemSet {
+        ItemSet((1 << 52) - 1)
+    }
+
+    fn from_items(items: &str) -> ItemSet {
+        let mut out = ItemSet::empty();
+        for c in items.chars() {
+            out.insert(c);
+        }
+        out
+    }
+
+    fn insert(&mut self, c: char) {
+        assert!(c.is_ascii_alphabetic(), "Not an item: {:?}", c);
+        self.0 |= 1 << (priority(c) - 1);
+    }
+
+    fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    // the one item in the set, if there's exactly one
+    fn single(self) -> Option<char> {
+        if self.len() == 1 {
+            self.items().next()
+        } else {
+            None
+        }
+    }
 
-    let val = b_with_input(&contents);
+    // items in increasing order of priority
+    fn items(self) -> impl Iterator<Item = char> {
+        let mut bits = self.0;
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let low = bits.trailing_zeros() as i32;
+            bits &= bits - 1;
+            Some(item_with_priority(low + 1))
+        })
+    }
+}
+
+impl BitAnd for ItemSet {
+    type Output = ItemSet;
+
+    fn bitand(self, rhs: ItemSet) -> ItemSet {
+        ItemSet(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for ItemSet {
+    type Output = ItemSet;
+
+    fn bitor(self, rhs: ItemSet) -> ItemSet {
+        ItemSet(self.0 | rhs.0)
+    }
+}
+
+impl Debug for ItemSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ItemSet({})", self.items().collect::<String>())
+    }
+}
+
+// the items which show up in every one of the rucksacks
+fn common_items<'a>(rucksacks: impl IntoIterator<Item = &'a str>) -> ItemSet {
+    rucksacks
+        .into_iter()
+        .map(ItemSet::from_items)
+        .fold(ItemSet::all(), |acc, next| acc & next)
+}
+
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
+
+    let group_size = params.get("group_size", 3);
+
+    let val = b_with_input(&contents, group_size);
 
     val.to_string()
 }
 
-fn b_with_input(input: &str) -> i32 {
-    let mut chunk = Vec::with_capacity(3);
+fn b_with_input(input: &str, group_size: usize) -> i32 {
+    let mut chunk = Vec::with_capacity(group_size);
 
     let mut total = 0;
 
@@ -70,7 +158,7 @@ fn b_with_input(input: &str) -> i32 {
         }
 
         chunk.push(line);
-        if chunk.len() == 3 {
+        if chunk.len() == group_size {
             let dupe = b_chunk_dupe(&chunk);
             let dupe_pri = priority(dupe);
             total += dupe_pri;
@@ -78,23 +166,17 @@ fn b_with_input(input: &str) -> i32 {
         }
     }
 
-    if chunk.len() != 0 {
-        unimplemented!("Bad input -- should have #lines % 3 == 0")
+    if !chunk.is_empty() {
+        unimplemented!("Bad input -- should have #lines % {} == 0", group_size)
     }
 
     total
 }
 
-fn b_chunk_dupe(lines: &Vec<&str>) -> char {
-    assert_eq!(lines.len(), 3);
-
-    let a: HashSet<char> = lines[0].chars().collect();
-    let b: HashSet<char> = lines[1].chars().filter(|ch| a.contains(ch)).collect();
-    let c: HashSet<char> = lines[2].chars().filter(|ch| b.contains(ch)).collect();
-
-    assert_eq!(c.len(), 1);
-
-    c.into_iter().next().unwrap()
+fn b_chunk_dupe(lines: &[&str]) -> char {
+    common_items(lines.iter().copied())
+        .single()
+        .expect("Group should have exactly one item in common")
 }
 
 #[cfg(test)]
@@ -126,13 +208,13 @@ CrZsJsPPZsGzwwsLwLmpwMDw";
 jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
 PmmdzqPrVvPwwTWBwg";
 
-        assert_eq!(b_with_input(input), 18);
+        assert_eq!(b_with_input(input, 3), 18);
 
         let input = "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
 ttgJtRGJQctTZtZT
 CrZsJsPPZsGzwwsLwLmpwMDw";
 
-        assert_eq!(b_with_input(input), 52);
+        assert_eq!(b_with_input(input, 3), 52);
 
         let input = "vJrwpWtwJgWrhcsFMMfFFhFp
 jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
@@ -141,6 +223,42 @@ wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
 ttgJtRGJQctTZtZT
 CrZsJsPPZsGzwwsLwLmpwMDw";
 
-        assert_eq!(b_with_input(input), 70);
+        assert_eq!(b_with_input(input, 3), 70);
+    }
+
+    #[test]
+    fn item_sets() {
+        let a = ItemSet::from_items("vJrwpWtwJgWr");
+        let b = ItemSet::from_items("hcsFMMfFFhFp");
+
+        assert_eq!((a & b).items().collect::<String>(), "p");
+        assert_eq!((a & b).single(), Some('p'));
+        assert_eq!(a.len(), 8);
+        assert_eq!((a | b).len(), 14);
+
+        // iteration is in priority order, so lowercase before uppercase
+        assert_eq!(
+            ItemSet::from_items("Zaza").items().collect::<String>(),
+            "azZ"
+        );
+
+        for p in 1..=52 {
+            assert_eq!(priority(item_with_priority(p)), p);
+        }
+    }
+
+    #[test]
+    fn other_group_sizes() {
+        let input = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL";
+
+        assert_eq!(
+            common_items(input.lines()).items().collect::<String>(),
+            "frsFM"
+        );
+
+        let input = "abc\nbxd\nAbZ\nzZz";
+        assert_eq!(b_with_input(input, 2), priority('b') + priority('Z'));
+        assert_eq!(b_with_input("abcQ\nQxd\nAQZ\nzQz", 4), priority('Q'));
     }
 }
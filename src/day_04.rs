@@ -1,9 +1,16 @@
-fn input() -> String {
-    std::fs::read_to_string("input/input_04.txt").expect("Should be able to read the file")
+use nom::IResult;
+
+use crate::interval::Interval;
+use crate::normalize::Whitespace;
+use crate::params::Params;
+use crate::parsing::{coord2, parse_all, unsigned};
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_04.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
 
     let val = a_with_input(&contents);
 
@@ -19,38 +26,26 @@ fn a_with_input(input: &str) -> usize {
 }
 
 fn a_line(line: &str) -> bool {
-    let ((left0, left1), (right0, right1)) = parse_line(line);
-
-    if left0 <= right0 && left1 >= right1 {
-        true
-    } else if right0 <= left0 && right1 >= left1 {
-        true
-    } else {
-        false
-    }
+    let (left, right) = parse_line(line);
+
+    left.contains_interval(right) || right.contains_interval(left)
 }
 
-fn parse_line(line: &str) -> ((i32, i32), (i32, i32)) {
-    let assignments: Vec<&str> = line.trim().split(",").collect();
-    assert_eq!(assignments.len(), 2, "Bad input: {}", line);
-    let left: Vec<i32> = assignments[0]
-        .split("-")
-        .map(|token| token.parse::<i32>().expect("Should parse"))
-        .collect();
-    assert_eq!(left.len(), 2);
-    assert!(left[0] <= left[1]);
-    let right: Vec<i32> = assignments[1]
-        .split("-")
-        .map(|token| token.parse::<i32>().expect("Should parse"))
-        .collect();
-    assert_eq!(right.len(), 2);
-    assert!(right[0] <= right[1]);
-
-    ((left[0], left[1]), (right[0], right[1]))
+// each line is two closed ranges, like 2-4,6-8
+fn parse_line(line: &str) -> (Interval, Interval) {
+    fn range(input: &str) -> IResult<&str, Interval> {
+        let (input, (first, last)) = coord2("-", unsigned::<i64>)(input)?;
+        Ok((input, Interval::closed(first, last)))
+    }
+
+    let (left, right) = parse_all(coord2(",", range), line.trim()).unwrap();
+    assert!(!left.is_empty() && !right.is_empty(), "Bad input: {}", line);
+
+    (left, right)
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
 
     let val = b_with_input(&contents);
 
@@ -66,22 +61,9 @@ fn b_with_input(input: &str) -> usize {
 }
 
 fn b_line(line: &str) -> bool {
-    let ((left0, left1), (right0, right1)) = parse_line(line);
-
-    // it is a fact that the intervals overlap iff one of the endpoints of one of the intervals
-    // is contained in the other interval
-    // i feel like these conditions are redundant but didn't quite crack it
-    if left0 >= right0 && left0 <= right1 {
-        true
-    } else if left1 >= right0 && left1 <= right1 {
-        true
-    } else if right0 >= left0 && right0 <= left1 {
-        true
-    } else if right1 >= left0 && right1 <= left1 {
-        true
-    } else {
-        false
-    }
+    let (left, right) = parse_line(line);
+
+    left.overlaps(right)
 }
 
 #[cfg(test)]
@@ -1,28 +1,60 @@
-use itertools::Itertools;
+use nom::{bytes::complete::tag, sequence::tuple, IResult};
 use std::collections::VecDeque;
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_05.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+use crate::parsing::{blocks, parse_all, unsigned};
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_05.txt", Whitespace::Significant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
+
+    // writes the input back out in the puzzle's layout, e.g. to repair padding an editor stripped
+    if params.get("reformat", false) {
+        let (stacks, moves) = parse_input(&contents);
+        return format_input(&stacks, &moves);
+    }
 
-    let val = a_with_input(&contents);
+    let show = params.get("show", false);
 
-    val.to_string()
+    a_with_input(&contents, show)
 }
 
-fn a_with_input(input: &str) -> String {
-    let mut lines = input.lines();
+fn a_with_input(input: &str, show: bool) -> String {
+    run_crane(input, CrateMover9000, show)
+}
+
+// runs all the moves and reports the top crates; with `show`, prints the drawing after every step
+fn run_crane<C: CraneModel>(input: &str, crane: C, show: bool) -> String {
+    let (stacks, moves) = parse_input(input);
+
+    let mut yard = CrateYard::new(stacks, crane);
+
+    if !show {
+        yard.apply_all(&moves)
+            .unwrap_or_else(|(idx, e)| panic!("Move {} is impossible: {}", idx + 1, e));
+        return make_output(yard.stacks());
+    }
+
+    println!("{}\n", yard.render());
 
-    let mut stacks = parse_stacks(&mut lines);
+    for (idx, m) in moves.iter().copied().enumerate() {
+        yard.apply(m)
+            .unwrap_or_else(|e| panic!("Move {} is impossible: {}", idx + 1, e));
 
-    for next_move in lines.map(|line| parse_move(line)) {
-        apply_move_a(&mut stacks, next_move);
+        println!(
+            "move {} from {} to {}\n{}\n",
+            m.num_to_move,
+            m.from_col + 1,
+            m.to_col + 1,
+            yard.render()
+        );
     }
 
-    make_output(&stacks)
+    make_output(yard.stacks())
 }
 
 fn make_output(stacks: &[VecDeque<char>]) -> String {
@@ -37,38 +69,91 @@ fn make_output(stacks: &[VecDeque<char>]) -> String {
     out_str
 }
 
-// parses the stacks and leaves the iterator in a state where the next line is a MOVE line
-fn parse_stacks<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Vec<VecDeque<char>> {
-    let mut stacks: Vec<VecDeque<char>> = Vec::new();
+// the input is the stacks drawing, a blank line, then the moves
+fn parse_input(input: &str) -> (Vec<VecDeque<char>>, Vec<Move>) {
+    let mut blocks = blocks(input);
+
+    let stacks = parse_stacks(blocks.next().expect("Should have a stacks drawing"));
+    let moves = blocks
+        .next()
+        .map(|block| block.lines().map(parse_move).collect())
+        .unwrap_or_default();
+
+    assert_eq!(blocks.next(), None, "Should only have stacks and moves");
+
+    (stacks, moves)
+}
+
+// The ' 1   2   3   4 ' footer says which character column each stack is drawn in, so we go by
+// that rather than by counting. Trailing padding (which editors like to strip) is just treated as
+// empty space. A crate line that lost its leading padding shows up as floating crates; if there's
+// exactly one way to shift it back onto the crates below, we do that, and otherwise give up.
+fn parse_stacks(drawing: &str) -> Vec<VecDeque<char>> {
+    let lines: Vec<&str> = drawing.lines().collect();
+    let (footer, crate_lines) = lines
+        .split_last()
+        .expect("Should have at least the footer line");
+
+    let stack_positions: Vec<usize> = footer
+        .char_indices()
+        .filter(|(_, c)| c.is_ascii_digit())
+        .map(|(i, _)| i)
+        .collect();
+    let num_stacks = stack_positions.len();
+
+    // filled[i] is whether stack i has a crate at the current height; we go bottom-up so we can
+    // tell if a crate would be floating
+    let mut filled: Vec<bool> = vec![true; num_stacks];
+    let mut rows: Vec<Vec<Option<char>>> = Vec::with_capacity(crate_lines.len());
+
+    for line in crate_lines.iter().rev() {
+        let chars: Vec<char> = line.chars().collect();
+
+        let row_with_shift = |shift: usize| -> Option<Vec<Option<char>>> {
+            let mut row: Vec<Option<char>> = vec![None; num_stacks];
+            for (i, c) in chars.iter().copied().enumerate() {
+                if !c.is_ascii_alphabetic() {
+                    continue;
+                }
+                let stack = stack_positions.iter().position(|&p| p == i + shift)?;
+                if !filled[stack] {
+                    return None;
+                }
+                row[stack] = Some(c);
+            }
+            Some(row)
+        };
 
-    // first, parse the stacks
-    loop {
-        let Some(line) = lines.next() else {
-            panic!("unexpected EOF");
+        let row = match row_with_shift(0) {
+            Some(row) => row,
+            None => {
+                let candidates: Vec<Vec<Option<char>>> = (1..num_stacks)
+                    .filter_map(|k| row_with_shift(4 * k))
+                    .collect();
+                assert_eq!(
+                    candidates.len(),
+                    1,
+                    "Crate line {:?} doesn't line up with the stacks below it",
+                    line
+                );
+                candidates.into_iter().next().unwrap()
+            }
         };
 
-        if !line.contains('[') {
-            // we don't actually need to do anything with the ' 1   2   3   4 ' line
-            // so skip that one and the blank line following
-            lines.next().expect("Should have a blank line");
-            break;
+        for (stack, c) in row.iter().enumerate() {
+            filled[stack] = c.is_some();
         }
+        rows.push(row);
+    }
 
-        let mut column = 0;
-        for token in &line.chars().chunks(4) {
-            while stacks.len() <= column {
-                stacks.push(VecDeque::new());
-            }
+    let mut stacks: Vec<VecDeque<char>> = vec![VecDeque::new(); num_stacks];
 
-            // we know (don't validate) that each column is 0 or 1 character
-            if let Some(c) = token
-                .filter(|&c| c != '[' && c != ']' && !c.is_ascii_whitespace())
-                .next()
-            {
-                stacks[column].push_back(c);
+    // rows are bottom-up, but the front of each stack is the top
+    for row in rows {
+        for (stack, c) in row.into_iter().enumerate() {
+            if let Some(c) = c {
+                stacks[stack].push_front(c);
             }
-            // moves are 1-indexed but our vector is 0-indexed so we increment at the end
-            column += 1;
         }
     }
 
@@ -83,99 +168,266 @@ struct Move {
 }
 
 fn parse_move(line: &str) -> Move {
-    let tokens: Vec<usize> = line
-        // this is the laziest parser but ugh i have shit to do
-        .replace("move ", "")
-        .replace(" from ", ",")
-        .replace(" to ", ",")
-        .split(",")
-        .map(|tok| tok.parse::<usize>().expect("Should parse"))
-        .collect();
+    fn helper(input: &str) -> IResult<&str, Move> {
+        let (input, (_, num_to_move, _, from_col, _, to_col)) = tuple((
+            tag("move "),
+            unsigned,
+            tag(" from "),
+            unsigned::<usize>,
+            tag(" to "),
+            unsigned::<usize>,
+        ))(input)?;
+
+        Ok((
+            input,
+            Move {
+                num_to_move,
+                // input is 1-indexed but we want 0-indexed
+                from_col: from_col - 1,
+                to_col: to_col - 1,
+            },
+        ))
+    }
+
+    parse_all(helper, line).unwrap()
+}
+
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
 
-    assert_eq!(tokens.len(), 3);
+    let show = params.get("show", false);
+    // by default the crane can lift any number of crates at once
+    let capacity: usize = params.get("capacity", 0);
 
-    Move {
-        num_to_move: tokens[0],
-        // input is 1-indexed but we want 0-indexed
-        from_col: tokens[1] - 1,
-        to_col: tokens[2] - 1,
+    b_with_input(&contents, capacity, show)
+}
+
+fn b_with_input(input: &str, capacity: usize, show: bool) -> String {
+    if capacity == 0 {
+        run_crane(input, CrateMover9001, show)
+    } else {
+        run_crane(input, CapacityLimitedCrane { capacity }, show)
     }
 }
 
-fn apply_move_a(stacks: &mut Vec<VecDeque<char>>, m: Move) {
-    if m.from_col == m.to_col {
-        return;
+// How a crane carries out a move: as a series of lifts, each of which picks up some crates from
+// the top of one stack and puts them down, in the same order, on top of another. The sizes of
+// the lifts always add up to the number of crates being moved.
+trait CraneModel {
+    fn lifts(&self, num_to_move: usize) -> Vec<usize>;
+}
+
+// e.g. if we move 3 crates from a stack which is (top first) A B C D E F G, onto a stack which
+// is V W X Y Z, then the 9000 goes one at a time, so we end up with C B A V W X Y Z
+struct CrateMover9000;
+
+impl CraneModel for CrateMover9000 {
+    fn lifts(&self, num_to_move: usize) -> Vec<usize> {
+        vec![1; num_to_move]
     }
+}
 
-    // e.g. if from_col is 0, to_col is 1, and we want to move 3 things
-    // and col 0 is:    A B C D E F G
-    // and col 1 is:    V W X Y Z
-    // the goal should be:
-    // col 0 is:        E F G
-    // col 1 is:        C B A V W X Y Z
+// while the 9001 takes them all at once, so we end up with A B C V W X Y Z
+struct CrateMover9001;
 
-    let mut temp = VecDeque::with_capacity(m.num_to_move);
-    for _ in 0..m.num_to_move {
-        temp.push_back(stacks[m.from_col].pop_front().unwrap());
+impl CraneModel for CrateMover9001 {
+    fn lifts(&self, num_to_move: usize) -> Vec<usize> {
+        if num_to_move == 0 {
+            vec![]
+        } else {
+            vec![num_to_move]
+        }
     }
+}
+
+// takes as many as it can carry at a time; with capacity 2, the example ends up C A B V W X Y Z
+struct CapacityLimitedCrane {
+    capacity: usize,
+}
 
-    // temp now contains the top N elements from the "from_col"
-    // in our example we're now at this state:
-    // col 0 is:        E F G
-    // temp  is:        A B C
-    // col 1 is:        V W X Y Z
+impl CraneModel for CapacityLimitedCrane {
+    fn lifts(&self, num_to_move: usize) -> Vec<usize> {
+        assert!(self.capacity > 0, "Crane has to be able to lift something");
 
-    for _ in 0..m.num_to_move {
-        stacks[m.to_col].push_front(temp.pop_front().unwrap());
+        let mut out = vec![self.capacity; num_to_move / self.capacity];
+        if !num_to_move.is_multiple_of(self.capacity) {
+            out.push(num_to_move % self.capacity);
+        }
+        out
     }
 }
 
-pub fn b() -> String {
-    let contents = input();
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum MoveError {
+    NoSuchStack(usize),
+    NotEnoughCrates {
+        stack: usize,
+        wanted: usize,
+        available: usize,
+    },
+}
 
-    let val = b_with_input(&contents);
+impl std::fmt::Display for MoveError {
+    // stacks are shown 1-indexed, like the input
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveError::NoSuchStack(stack) => write!(f, "There is no stack {}", stack + 1),
+            MoveError::NotEnoughCrates {
+                stack,
+                wanted,
+                available,
+            } => write!(
+                f,
+                "Wanted to move {} crates from stack {}, but it only has {}",
+                wanted,
+                stack + 1,
+                available
+            ),
+        }
+    }
+}
 
-    val.to_string()
+// The stacks, plus a record of everything done to them so far, so moves can be undone.
+struct CrateYard<C: CraneModel> {
+    // front of each stack is the top
+    stacks: Vec<VecDeque<char>>,
+    crane: C,
+    // each move that's been made, with the lifts it was made in
+    history: Vec<(Move, Vec<usize>)>,
 }
 
-fn b_with_input(input: &str) -> String {
-    let mut lines = input.lines();
+impl<C: CraneModel> CrateYard<C> {
+    fn new(stacks: Vec<VecDeque<char>>, crane: C) -> CrateYard<C> {
+        CrateYard {
+            stacks,
+            crane,
+            history: Vec::new(),
+        }
+    }
+
+    fn stacks(&self) -> &[VecDeque<char>] {
+        &self.stacks
+    }
 
-    let mut stacks = parse_stacks(&mut lines);
+    // checks the move is possible before touching anything, so a bad move leaves things as they
+    // were
+    fn apply(&mut self, m: Move) -> Result<(), MoveError> {
+        for stack in [m.from_col, m.to_col] {
+            if stack >= self.stacks.len() {
+                return Err(MoveError::NoSuchStack(stack));
+            }
+        }
+
+        let available = self.stacks[m.from_col].len();
+        if available < m.num_to_move {
+            return Err(MoveError::NotEnoughCrates {
+                stack: m.from_col,
+                wanted: m.num_to_move,
+                available,
+            });
+        }
 
-    for next_move in lines.map(|line| parse_move(line)) {
-        apply_move_b(&mut stacks, next_move);
+        let lifts = self.crane.lifts(m.num_to_move);
+        for &size in &lifts {
+            self.lift(m.from_col, m.to_col, size);
+        }
+        self.history.push((m, lifts));
+
+        Ok(())
     }
 
-    make_output(&stacks)
-}
+    // applies the moves in order, all or nothing: if any of them is bad, the ones before it are
+    // taken back, and the error says which one it was
+    fn apply_all(&mut self, moves: &[Move]) -> Result<(), (usize, MoveError)> {
+        for (idx, m) in moves.iter().copied().enumerate() {
+            if let Err(e) = self.apply(m) {
+                for _ in 0..idx {
+                    self.undo();
+                }
+                return Err((idx, e));
+            }
+        }
+        Ok(())
+    }
 
-fn apply_move_b(stacks: &mut Vec<VecDeque<char>>, m: Move) {
-    if m.from_col == m.to_col {
-        return;
+    // takes back the last move, returning it, or None if nothing has been done yet
+    fn undo(&mut self) -> Option<Move> {
+        let (m, lifts) = self.history.pop()?;
+
+        // each lift is undone by lifting the same crates straight back
+        for &size in lifts.iter().rev() {
+            self.lift(m.to_col, m.from_col, size);
+        }
+
+        Some(m)
+    }
+
+    fn lift(&mut self, from: usize, to: usize, size: usize) {
+        if from == to {
+            return;
+        }
+
+        let lifted: Vec<char> = self.stacks[from].drain(..size).collect();
+        for c in lifted.into_iter().rev() {
+            self.stacks[to].push_front(c);
+        }
     }
 
-    // e.g. if from_col is 0, to_col is 1, and we want to move 3 things
-    // and col 0 is:    A B C D E F G
-    // and col 1 is:    V W X Y Z
-    // the goal should be:
-    // col 0 is:        E F G
-    // col 1 is:        A B C V W X Y Z
+    fn render(&self) -> String {
+        draw_stacks(&self.stacks)
+    }
+}
 
-    let mut temp = VecDeque::with_capacity(m.num_to_move);
-    for _ in 0..m.num_to_move {
-        temp.push_back(stacks[m.from_col].pop_front().unwrap());
+// The inverse of parse_stacks: draws the stacks the way the puzzle does, with every line padded
+// out to the full width, and the numbered footer on the bottom. The footer only has room for
+// single-digit stack numbers, so that's all we can draw.
+fn draw_stacks(stacks: &[VecDeque<char>]) -> String {
+    assert!(
+        stacks.len() <= 9,
+        "Can't draw {} stacks; the footer only fits 9",
+        stacks.len()
+    );
+
+    let height = stacks.iter().map(|s| s.len()).max().unwrap_or(0);
+
+    let mut lines: Vec<String> = Vec::with_capacity(height + 1);
+
+    for level in (0..height).rev() {
+        let cells: Vec<String> = stacks
+            .iter()
+            .map(|stack| {
+                // level 0 is the bottom, which is the back of the deque
+                if level < stack.len() {
+                    format!("[{}]", stack[stack.len() - 1 - level])
+                } else {
+                    "   ".to_string()
+                }
+            })
+            .collect();
+        lines.push(cells.join(" "));
     }
 
-    // temp now contains the top N elements from the "from_col"
-    // in our example we're now at this state:
-    // col 0 is:        E F G
-    // temp  is:        A B C
-    // col 1 is:        V W X Y Z
+    let footer: Vec<String> = (1..=stacks.len()).map(|i| format!(" {} ", i)).collect();
+    lines.push(footer.join(" "));
 
-    for _ in 0..m.num_to_move {
-        stacks[m.to_col].push_front(temp.pop_back().unwrap());
+    lines.join("\n")
+}
+
+// The inverse of parse_input, e.g. for writing out generated or repaired inputs
+fn format_input(stacks: &[VecDeque<char>], moves: &[Move]) -> String {
+    let mut out = draw_stacks(stacks);
+    out.push_str("\n\n");
+
+    for m in moves {
+        out.push_str(&format!(
+            "move {} from {} to {}\n",
+            m.num_to_move,
+            m.from_col + 1,
+            m.to_col + 1
+        ));
     }
+
+    out
 }
 
 #[cfg(test)]
@@ -194,7 +446,7 @@ move 3 from 1 to 3
 move 2 from 2 to 1
 move 1 from 1 to 2";
 
-        assert_eq!(a_with_input(input), "CMZ");
+        assert_eq!(a_with_input(input, false), "CMZ");
     }
 
     #[test]
@@ -209,6 +461,208 @@ move 3 from 1 to 3
 move 2 from 2 to 1
 move 1 from 1 to 2";
 
-        assert_eq!(b_with_input(input), "MCD");
+        assert_eq!(b_with_input(input, 0, false), "MCD");
+    }
+
+    #[test]
+    fn stripped_padding() {
+        let expected: Vec<VecDeque<char>> = vec![
+            VecDeque::from(vec!['N', 'Z']),
+            VecDeque::from(vec!['D', 'C', 'M']),
+            VecDeque::from(vec!['P']),
+        ];
+
+        assert_eq!(
+            parse_stacks("    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 "),
+            expected
+        );
+        // trailing padding stripped by an editor
+        assert_eq!(
+            parse_stacks("    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3"),
+            expected
+        );
+        // leading padding stripped off the top line; D can't float over the empty stack 1,
+        // so the only place for it is stack 2
+        assert_eq!(
+            parse_stacks("[D]\n    [C]\n[Z] [M] [P]\n 1   2   3"),
+            vec![
+                VecDeque::from(vec!['Z']),
+                VecDeque::from(vec!['D', 'C', 'M']),
+                VecDeque::from(vec!['P']),
+            ]
+        );
+
+        // but if the stripped line still fits where it is, there's no way to tell
+        assert_eq!(
+            parse_stacks("[D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3"),
+            vec![
+                VecDeque::from(vec!['D', 'N', 'Z']),
+                VecDeque::from(vec!['C', 'M']),
+                VecDeque::from(vec!['P']),
+            ]
+        );
+
+        // CRLF input is handled before the days ever see it
+        let crlf = "    [D]\r\n[N] [C]\r\n[Z] [M] [P]\r\n 1   2   3\r\n\r\nmove 1 from 2 to 1\r\n";
+        let normalized = crate::normalize::normalize(crlf, Whitespace::Significant);
+        assert_eq!(parse_input(&normalized).0, expected);
+    }
+
+    #[test]
+    fn crane_models() {
+        let stacks = || {
+            vec![
+                "ABCDEFG".chars().collect::<VecDeque<char>>(),
+                "VWXYZ".chars().collect::<VecDeque<char>>(),
+            ]
+        };
+        let m = Move {
+            num_to_move: 3,
+            from_col: 0,
+            to_col: 1,
+        };
+
+        let mut yard = CrateYard::new(stacks(), CrateMover9000);
+        yard.apply(m).unwrap();
+        assert_eq!(
+            yard.stacks()[1],
+            "CBAVWXYZ".chars().collect::<VecDeque<_>>()
+        );
+
+        let mut yard = CrateYard::new(stacks(), CrateMover9001);
+        yard.apply(m).unwrap();
+        assert_eq!(
+            yard.stacks()[1],
+            "ABCVWXYZ".chars().collect::<VecDeque<_>>()
+        );
+
+        let mut yard = CrateYard::new(stacks(), CapacityLimitedCrane { capacity: 2 });
+        yard.apply(m).unwrap();
+        assert_eq!(yard.stacks()[0], "DEFG".chars().collect::<VecDeque<_>>());
+        assert_eq!(
+            yard.stacks()[1],
+            "CABVWXYZ".chars().collect::<VecDeque<_>>()
+        );
+
+        assert_eq!(yard.undo(), Some(m));
+        assert_eq!(yard.stacks(), &stacks()[..]);
+        assert_eq!(yard.undo(), None);
+    }
+
+    #[test]
+    fn bad_moves_and_replay() {
+        let input = "    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2";
+        let (stacks, moves) = parse_input(input);
+
+        let mut yard = CrateYard::new(stacks, CrateMover9000);
+        let start = yard.render();
+        assert_eq!(start, "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ");
+
+        yard.apply(moves[0]).unwrap();
+        assert_eq!(
+            yard.render(),
+            "[D]        \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 "
+        );
+
+        // stack 2 only has 2 crates left; nothing should change
+        let too_many = Move {
+            num_to_move: 3,
+            from_col: 1,
+            to_col: 0,
+        };
+        assert_eq!(
+            yard.apply(too_many),
+            Err(MoveError::NotEnoughCrates {
+                stack: 1,
+                wanted: 3,
+                available: 2
+            })
+        );
+        assert_eq!(
+            yard.apply(Move {
+                num_to_move: 1,
+                from_col: 0,
+                to_col: 3
+            }),
+            Err(MoveError::NoSuchStack(3))
+        );
+
+        yard.apply_all(&moves[1..]).unwrap();
+        assert_eq!(make_output(yard.stacks()), "CMZ");
+
+        while yard.undo().is_some() {}
+        assert_eq!(yard.render(), start);
+
+        let mut bad_moves = moves.clone();
+        bad_moves.insert(2, too_many);
+        // the whole list stops at the bad move, and leaves the stacks as they were
+        let mut yard = CrateYard::new(parse_input(input).0, CrateMover9001);
+        assert_eq!(yard.apply_all(&bad_moves).unwrap_err().0, 2);
+        assert_eq!(yard.render(), start);
+    }
+
+    #[test]
+    fn drawing_round_trip() {
+        let drawing = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+        assert_eq!(draw_stacks(&parse_stacks(drawing)), drawing);
+
+        let awkward: Vec<Vec<VecDeque<char>>> = vec![
+            // nothing at all
+            vec![VecDeque::new(); 4],
+            // empty stacks at either end, and one tall one
+            vec![
+                VecDeque::new(),
+                "ABCDEFGH".chars().collect(),
+                "Q".chars().collect(),
+                VecDeque::new(),
+            ],
+            vec!["Z".chars().collect(); 9],
+        ];
+
+        for stacks in awkward {
+            assert_eq!(parse_stacks(&draw_stacks(&stacks)), stacks);
+        }
+
+        let input = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\nmove 3 from 1 to 3\n";
+        let (stacks, moves) = parse_input(input);
+        assert_eq!(format_input(&stacks, &moves), input);
+    }
+
+    #[test]
+    fn worked_example() {
+        let input = "    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2";
+
+        // the states shown in the puzzle after each move; the puzzle trims trailing spaces
+        // off the last line, and so do we, to compare
+        let expected = [
+            "[D]        \n[N] [C]    \n[Z] [M] [P]\n 1   2   3",
+            "        [Z]\n        [N]\n    [C] [D]\n    [M] [P]\n 1   2   3",
+            "        [Z]\n        [N]\n[M]     [D]\n[C]     [P]\n 1   2   3",
+            "        [Z]\n        [N]\n        [D]\n[C] [M] [P]\n 1   2   3",
+        ];
+
+        let (stacks, moves) = parse_input(input);
+        let mut yard = CrateYard::new(stacks, CrateMover9000);
+
+        for (m, expected) in moves.iter().copied().zip(expected) {
+            yard.apply(m).unwrap();
+            assert_eq!(yard.render().trim_end(), expected);
+        }
     }
 }
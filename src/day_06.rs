@@ -1,86 +1,133 @@
 use std::collections::VecDeque;
+use std::io::{BufReader, Bytes, Read};
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_06.txt").expect("Should be able to read the file")
+use itertools::Itertools;
+
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_06.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
+
+    if params.get("all", false) {
+        return all_markers(&contents, 4);
+    }
 
     let val = a_with_input(&contents);
 
     val.to_string()
 }
 
-fn a_with_input(input: &str) -> usize {
-    let mut chars = input.chars();
-
-    let mut seen = VecDeque::new();
-
-    for _ in 0..4 {
-        seen.push_back(chars.next().expect("Must have length >= 4"));
-    }
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
 
-    fn is_done(seen: &VecDeque<char>) -> bool {
-        assert_eq!(seen.len(), 4);
-        let mut v: Vec<_> = seen.iter().copied().collect();
-        v.sort();
-        v[0] < v[1] && v[1] < v[2] && v[2] < v[3]
+    if params.get("all", false) {
+        return all_markers(&contents, 14);
     }
 
-    let mut c_ind = 4;
-    while let Some(next) = chars.next() {
-        c_ind += 1;
-        seen.pop_front().unwrap();
-        seen.push_back(next);
-        if is_done(&seen) {
-            return c_ind;
-        }
-    }
+    let val = b_with_input(&contents);
 
-    unimplemented!("bad input")
+    val.to_string()
 }
 
-pub fn b() -> String {
-    let contents = input();
-
-    let val = b_with_input(&contents);
+// for --param all=true, which lists every marker position rather than just the first
+fn all_markers(input: &str, window: usize) -> String {
+    markers(input.as_bytes(), window)
+        .map(|pos| pos.expect("Should be able to read the input"))
+        .join(",")
+}
 
-    val.to_string()
+fn a_with_input(input: &str) -> usize {
+    find_marker(input.as_bytes(), 4)
+        .expect("Should be able to read the input")
+        .expect("No marker in the input")
 }
 
 fn b_with_input(input: &str) -> usize {
-    let mut chars = input.chars();
+    find_marker(input.as_bytes(), 14)
+        .expect("Should be able to read the input")
+        .expect("No marker in the input")
+}
 
-    let mut seen = VecDeque::new();
+// How many characters have to be read before the last `window` of them are all different, if
+// that ever happens
+fn find_marker<R: Read>(reader: R, window: usize) -> std::io::Result<Option<usize>> {
+    markers(reader, window).next().transpose()
+}
 
-    for _ in 0..14 {
-        seen.push_back(chars.next().expect("Must have length >= 4"));
+// Every position at which the last `window` characters are all different, in increasing order.
+// The datastream is a single line, so it ends at the first newline (or the end of the input).
+fn markers<R: Read>(reader: R, window: usize) -> Markers<R> {
+    assert!(window > 0, "Window has to have something in it");
+
+    Markers {
+        bytes: BufReader::new(reader).bytes(),
+        window,
+        seen: VecDeque::with_capacity(window + 1),
+        counts: [0; 256],
+        num_distinct: 0,
+        num_read: 0,
+        done: false,
     }
+}
 
-    fn is_done(seen: &VecDeque<char>) -> bool {
-        assert_eq!(seen.len(), 14);
-        let mut v: Vec<_> = seen.iter().copied().collect();
-        v.sort();
-        for i in 0..13 {
-            if v[i] == v[i + 1] {
-                return false;
+// Keeps a count of each byte in the current window, and how many of those counts are nonzero,
+// so each new byte is O(1) work whatever the window size.
+struct Markers<R: Read> {
+    bytes: Bytes<BufReader<R>>,
+    window: usize,
+    // the last (up to) `window` bytes, oldest first
+    seen: VecDeque<u8>,
+    counts: [usize; 256],
+    num_distinct: usize,
+    num_read: usize,
+    done: bool,
+}
+
+impl<R: Read> Iterator for Markers<R> {
+    type Item = std::io::Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let next = match self.bytes.next() {
+                Some(Ok(b'\n')) | None => {
+                    self.done = true;
+                    return None;
+                }
+                Some(Err(e)) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+                Some(Ok(next)) => next,
+            };
+
+            self.num_read += 1;
+
+            self.seen.push_back(next);
+            self.counts[next as usize] += 1;
+            if self.counts[next as usize] == 1 {
+                self.num_distinct += 1;
             }
-        }
-        true
-    }
 
-    let mut c_ind = 14;
-    while let Some(next) = chars.next() {
-        c_ind += 1;
-        seen.pop_front().unwrap();
-        seen.push_back(next);
-        if is_done(&seen) {
-            return c_ind;
+            if self.seen.len() > self.window {
+                let old = self.seen.pop_front().unwrap();
+                self.counts[old as usize] -= 1;
+                if self.counts[old as usize] == 0 {
+                    self.num_distinct -= 1;
+                }
+            }
+
+            if self.num_distinct == self.window {
+                return Some(Ok(self.num_read));
+            }
         }
-    }
 
-    unimplemented!("bad input")
+        None
+    }
 }
 
 #[cfg(test)]
@@ -104,4 +151,19 @@ mod tests {
         assert_eq!(b_with_input("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), 29);
         assert_eq!(b_with_input("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), 26);
     }
+
+    #[test]
+    fn every_marker() {
+        let positions: Vec<usize> = markers("aabcbcdd\n".as_bytes(), 3)
+            .map(Result::unwrap)
+            .collect();
+        // aab (no), abc, bcb (no), cbc (no), bcd, cdd (no)
+        assert_eq!(positions, vec![4, 7]);
+
+        // the very first window counts, and a window of 1 is everything
+        assert_eq!(find_marker("abcd".as_bytes(), 4).unwrap(), Some(4));
+        assert_eq!(markers("zzz".as_bytes(), 1).count(), 3);
+
+        assert_eq!(find_marker("abab\nabcd".as_bytes(), 3).unwrap(), None);
+    }
 }
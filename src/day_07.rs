@@ -1,11 +1,40 @@
-use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_07.txt").expect("Should be able to read the file")
+use itertools::Itertools;
+
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_07.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
+
+    // a few ways to poke around the filesystem instead of solving the puzzle
+    let largest: usize = params.get("largest", 0);
+    let glob: String = params.get("glob", String::new());
+    let tree = params.get("tree", false);
+    if tree || params.get("du", false) || largest > 0 || !glob.is_empty() {
+        let fs = FileSystem::from_terminal_output(&contents);
+        return if largest > 0 {
+            fs.largest_dirs(largest)
+                .into_iter()
+                .map(|(path, size)| format!("{}\t{}", size, path))
+                .join("\n")
+        } else if !glob.is_empty() {
+            fs.matching_files(&glob)
+                .into_iter()
+                .map(|(path, size)| format!("{}\t{}", size, path))
+                .join("\n")
+        } else if tree {
+            // as a block, so it lines up when printed
+            format!("\n{}", fs.to_tree().render(params.get("sizes", false)))
+        } else {
+            fs.du()
+        };
+    }
 
     let val = a_with_input(&contents);
 
@@ -36,8 +65,8 @@ fn a_with_input(input: &str) -> usize {
     traverse_tree(&root_dir)
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
 
     let val = b_with_input(&contents);
 
@@ -76,61 +105,225 @@ fn b_with_input(input: &str) -> usize {
 }
 
 fn parse_tree_from_input(input_str: &str) -> Directory {
-    let mut lines = input_str.trim().lines().peekable();
-
-    let first_line = lines.next().expect("Lines should be nonempty");
-
-    assert_eq!(first_line, "$ cd /");
-
-    let mut working_stack: Vec<Directory> = Vec::new();
-    let mut working_dir = Directory::new();
-
-    let into_dir_pattern = Regex::new(r"^\$ cd ([A-Za-z]+)$").expect("Regex should compile");
-    let dir_statement_pattern = Regex::new(r"^dir ([A-Za-z]+)$").expect("Regex should compile");
-    let file_size_pattern = Regex::new(r"^([0-9]+) ([A-Za-z\.]+)$").expect("Regex should compile");
-
-    // are we SURE we don't want a parsing library, hmmm
-    while let Some(line) = lines.next() {
-        if line == "$ cd .." {
-            assert!(!working_stack.is_empty(), "Should not popd at root");
-            let mut parent_dir = working_stack.pop().unwrap();
-            parent_dir.add_child(DiskObj::Dir(working_dir));
-            working_dir = parent_dir;
-        } else if into_dir_pattern.is_match(line) {
-            working_stack.push(working_dir);
-            // interestingly, we never need the directory name, so we don't need to parse it
-            // it does make printing the tree weird (which we also don't need to do, but still)
-            working_dir = Directory::new();
-        } else if line == "$ ls" {
-            while let Some(next_line) = lines.peek() {
-                if dir_statement_pattern.is_match(next_line) {
-                    // do nothing with the line; we'll write it down once we CD into it
-                    lines.next();
-                } else if file_size_pattern.is_match(next_line) {
-                    let capture = file_size_pattern.captures_iter(next_line).next().unwrap();
-                    let size: usize = capture[1].parse().unwrap();
-                    // note: intentionally skipping name here since we don't need it
-                    let file = File { size };
-                    working_dir.add_child(DiskObj::File(file));
-                    lines.next();
-                } else if next_line.starts_with('$') {
-                    // done with the ls results; don't consume this line, we'll want it later
-                    break;
-                } else {
-                    unimplemented!("Unexpected output while parsing ls: {}", next_line);
+    FileSystem::from_terminal_output(input_str).to_tree()
+}
+
+// One line of the terminal session
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum TerminalLine {
+    Cd(String),
+    Ls,
+    Dir(String),
+    File(usize, String),
+}
+
+// A path is the names of the directories from the root down, so the root itself is []
+type Path = Vec<String>;
+
+fn path_to_string(path: &[String]) -> String {
+    format!("/{}", path.join("/"))
+}
+
+#[derive(Default)]
+struct DirListing {
+    subdirs: BTreeSet<String>,
+    // name -> size
+    files: BTreeMap<String, usize>,
+}
+
+// Everything we've learned about the disk, keyed by path. Seeing the same directory (or file)
+// again just confirms what we already knew, so it doesn't matter how often the session goes
+// back to a directory or lists it.
+struct FileSystem {
+    dirs: BTreeMap<Path, DirListing>,
+}
+
+impl FileSystem {
+    fn from_terminal_output(input: &str) -> FileSystem {
+        let mut dirs: BTreeMap<Path, DirListing> = BTreeMap::new();
+        dirs.insert(Vec::new(), DirListing::default());
+
+        let mut cwd: Path = Vec::new();
+
+        for line in parse::parse_input(input) {
+            match line {
+                TerminalLine::Cd(name) if name == "/" => cwd.clear(),
+                TerminalLine::Cd(name) if name == ".." => {
+                    assert!(cwd.pop().is_some(), "Should not cd .. at root");
+                }
+                TerminalLine::Cd(name) => {
+                    dirs.get_mut(&cwd).unwrap().subdirs.insert(name.clone());
+                    cwd.push(name);
+                    dirs.entry(cwd.clone()).or_default();
+                }
+                // nothing to do until the output shows up
+                TerminalLine::Ls => {}
+                TerminalLine::Dir(name) => {
+                    dirs.get_mut(&cwd).unwrap().subdirs.insert(name.clone());
+                    let mut child = cwd.clone();
+                    child.push(name);
+                    dirs.entry(child).or_default();
+                }
+                TerminalLine::File(size, name) => {
+                    let old = dirs.get_mut(&cwd).unwrap().files.insert(name.clone(), size);
+                    assert!(
+                        old.is_none() || old == Some(size),
+                        "File {} changed size",
+                        name
+                    );
                 }
             }
-        } else {
-            unimplemented!("Bad input: {}", line);
         }
+
+        FileSystem { dirs }
+    }
+
+    // total size of everything in each directory, including subdirectories
+    fn dir_sizes(&self) -> BTreeMap<&Path, usize> {
+        let mut sizes: BTreeMap<&Path, usize> = BTreeMap::new();
+
+        // a path sorts after its parent, so going backwards does every directory after all of
+        // its subdirectories
+        for (path, listing) in self.dirs.iter().rev() {
+            let files: usize = listing.files.values().sum();
+            let subdirs: usize = listing
+                .subdirs
+                .iter()
+                .map(|name| {
+                    let mut child = path.clone();
+                    child.push(name.clone());
+                    sizes[&child]
+                })
+                .sum();
+            sizes.insert(path, files + subdirs);
+        }
+
+        sizes
+    }
+
+    // the n biggest directories, biggest first, with their total sizes
+    fn largest_dirs(&self, n: usize) -> Vec<(String, usize)> {
+        self.dir_sizes()
+            .into_iter()
+            .sorted_by_key(|(path, size)| (std::cmp::Reverse(*size), *path))
+            .take(n)
+            .map(|(path, size)| (path_to_string(path), size))
+            .collect()
+    }
+
+    // files whose full path matches the glob; `*` and `?` stay within one path component, while
+    // `**` can match any number of them
+    fn matching_files(&self, glob: &str) -> Vec<(String, usize)> {
+        let mut out = Vec::new();
+        for (path, listing) in self.dirs.iter() {
+            for (name, size) in listing.files.iter() {
+                let mut full_path = path.clone();
+                full_path.push(name.clone());
+                let full_path = path_to_string(&full_path);
+                if glob_matches(glob.as_bytes(), full_path.as_bytes()) {
+                    out.push((full_path, *size));
+                }
+            }
+        }
+        out
+    }
+
+    // every directory's total size, deepest first, the way `du -h` prints them
+    fn du(&self) -> String {
+        let sizes = self.dir_sizes();
+
+        let mut lines = Vec::new();
+        self.du_helper(&Vec::new(), &sizes, &mut lines);
+        lines.join("\n")
+    }
+
+    fn du_helper(&self, path: &Path, sizes: &BTreeMap<&Path, usize>, lines: &mut Vec<String>) {
+        for name in self.dirs[path].subdirs.iter() {
+            let mut child = path.clone();
+            child.push(name.clone());
+            self.du_helper(&child, sizes, lines);
+        }
+        lines.push(format!(
+            "{}\t{}",
+            human_size(sizes[path]),
+            path_to_string(path)
+        ));
+    }
+
+    fn to_tree(&self) -> Directory {
+        self.to_tree_helper(&Vec::new(), "/".to_string())
+    }
+
+    // children come out sorted by name, files and directories mixed together, like the puzzle
+    fn to_tree_helper(&self, path: &Path, name: String) -> Directory {
+        let listing = &self.dirs[path];
+
+        let mut children: Vec<DiskObj> = Vec::new();
+        for subdir in listing.subdirs.iter() {
+            let mut child = path.clone();
+            child.push(subdir.clone());
+            children.push(DiskObj::Dir(self.to_tree_helper(&child, subdir.clone())));
+        }
+        for (file_name, size) in listing.files.iter() {
+            children.push(DiskObj::File(File {
+                name: file_name.clone(),
+                size: *size,
+            }));
+        }
+        children.sort_by(|a, b| a.name().cmp(b.name()));
+
+        let mut dir = Directory::new(name);
+        for child in children {
+            dir.add_child(child);
+        }
+        dir
     }
+}
 
-    while let Some(mut parent_dir) = working_stack.pop() {
-        parent_dir.add_child(DiskObj::Dir(working_dir));
-        working_dir = parent_dir;
+// `*` is any run of characters other than '/', `**` is any run of characters at all, and `?` is
+// any one character other than '/'
+fn glob_matches(glob: &[u8], text: &[u8]) -> bool {
+    match glob {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_matches(rest, &text[i..])),
+        [b'*', rest @ ..] => {
+            let component_len = text.iter().position(|&c| c == b'/').unwrap_or(text.len());
+            (0..=component_len).any(|i| glob_matches(rest, &text[i..]))
+        }
+        [b'?', rest @ ..] => match text {
+            [c, text_rest @ ..] if *c != b'/' => glob_matches(rest, text_rest),
+            _ => false,
+        },
+        [g, rest @ ..] => match text {
+            [c, text_rest @ ..] if c == g => glob_matches(rest, text_rest),
+            _ => false,
+        },
     }
+}
 
-    working_dir
+// like `du -h`: powers of 1024, rounded up, with one decimal place below 10
+fn human_size(size: usize) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+
+    if size < 1024 {
+        return size.to_string();
+    }
+
+    let mut value = size as f64;
+    let mut unit = "";
+    for next_unit in UNITS {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next_unit;
+    }
+
+    if value < 10.0 {
+        format!("{:.1}{}", (value * 10.0).ceil() / 10.0, unit)
+    } else {
+        format!("{}{}", value.ceil(), unit)
+    }
 }
 
 enum DiskObj {
@@ -145,20 +338,30 @@ impl DiskObj {
             DiskObj::File(f) => f.size,
         }
     }
+
+    fn name(&self) -> &str {
+        match self {
+            DiskObj::Dir(d) => &d.name,
+            DiskObj::File(f) => &f.name,
+        }
+    }
 }
 
 struct File {
+    name: String,
     size: usize,
 }
 
 struct Directory {
+    name: String,
     contents: Vec<DiskObj>,
     size: usize,
 }
 
 impl Directory {
-    fn new() -> Directory {
+    fn new(name: String) -> Directory {
         Directory {
+            name,
             contents: Vec::new(),
             size: 0,
         }
@@ -168,6 +371,75 @@ impl Directory {
         self.size += child.size();
         self.contents.push(child);
     }
+
+    // The listing from the puzzle, e.g.
+    //     - / (dir)
+    //       - a (dir)
+    //         - i (file, size=584)
+    // with `with_sizes`, directories show their total size as well
+    fn render(&self, with_sizes: bool) -> String {
+        let mut lines = Vec::new();
+        self.render_helper(0, with_sizes, &mut lines);
+        lines.join("\n")
+    }
+
+    fn render_helper(&self, depth: usize, with_sizes: bool, lines: &mut Vec<String>) {
+        let indent = "  ".repeat(depth);
+
+        if with_sizes {
+            lines.push(format!(
+                "{}- {} (dir, size={})",
+                indent, self.name, self.size
+            ));
+        } else {
+            lines.push(format!("{}- {} (dir)", indent, self.name));
+        }
+
+        for child in self.contents.iter() {
+            match child {
+                DiskObj::Dir(d) => d.render_helper(depth + 1, with_sizes, lines),
+                DiskObj::File(f) => {
+                    lines.push(format!("{}  - {} (file, size={})", indent, f.name, f.size))
+                }
+            }
+        }
+    }
+}
+
+mod parse {
+    use nom::{
+        branch::alt,
+        bytes::complete::tag,
+        character::complete::not_line_ending,
+        combinator::{map, verify},
+        sequence::{preceded, separated_pair},
+        IResult,
+    };
+
+    use super::TerminalLine;
+    use crate::parsing::{parse_lines, unsigned};
+
+    fn name(input: &str) -> IResult<&str, String> {
+        map(
+            verify(not_line_ending, |s: &str| !s.is_empty()),
+            |s: &str| s.to_string(),
+        )(input)
+    }
+
+    fn parse_line_helper(input: &str) -> IResult<&str, TerminalLine> {
+        alt((
+            map(preceded(tag("$ cd "), name), TerminalLine::Cd),
+            map(tag("$ ls"), |_| TerminalLine::Ls),
+            map(preceded(tag("dir "), name), TerminalLine::Dir),
+            map(separated_pair(unsigned, tag(" "), name), |(size, name)| {
+                TerminalLine::File(size, name)
+            }),
+        ))(input)
+    }
+
+    pub(super) fn parse_input(input: &str) -> Vec<TerminalLine> {
+        parse_lines(parse_line_helper, input.trim()).unwrap()
+    }
 }
 
 #[cfg(test)]
@@ -207,4 +479,103 @@ $ ls
     fn sample_b() {
         assert_eq!(b_with_input(SAMPLE_INPUT_STR), 24933642);
     }
+
+    #[test]
+    fn revisits_and_absolute_cd() {
+        // the sample, but wandering around: lists a twice, goes back to the root with cd /,
+        // and visits d by way of the root
+        let input = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd /
+$ cd a
+$ cd e
+$ ls
+584 i
+$ cd /
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+        assert_eq!(a_with_input(input), 95437);
+        assert_eq!(b_with_input(input), 24933642);
+    }
+
+    #[test]
+    fn queries() {
+        let fs = FileSystem::from_terminal_output(SAMPLE_INPUT_STR);
+
+        assert_eq!(
+            fs.largest_dirs(2),
+            vec![("/".to_string(), 48381165), ("/d".to_string(), 24933642)]
+        );
+
+        assert_eq!(
+            fs.matching_files("/*.*"),
+            vec![
+                ("/b.txt".to_string(), 14848514),
+                ("/c.dat".to_string(), 8504156)
+            ]
+        );
+        assert_eq!(
+            fs.matching_files("**/?.*")
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>(),
+            vec!["/b.txt", "/c.dat", "/a/h.lst", "/d/d.ext", "/d/d.log"]
+        );
+        assert_eq!(fs.matching_files("/a/*").len(), 3);
+
+        assert_eq!(fs.du(), "584\t/a/e\n93K\t/a\n24M\t/d\n47M\t/");
+
+        assert_eq!(human_size(1024), "1.0K");
+        assert_eq!(human_size(1025), "1.1K");
+        assert_eq!(human_size(10 * 1024), "10K");
+    }
+
+    #[test]
+    fn render_tree() {
+        let expected = "- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)";
+
+        let tree = parse_tree_from_input(SAMPLE_INPUT_STR);
+        assert_eq!(tree.render(false), expected);
+
+        let with_sizes = tree.render(true);
+        assert!(with_sizes.starts_with("- / (dir, size=48381165)\n  - a (dir, size=94853)\n"));
+        assert!(with_sizes.contains("\n    - e (dir, size=584)\n"));
+        assert!(with_sizes.contains("\n  - d (dir, size=24933642)\n"));
+    }
 }
@@ -1,75 +1,71 @@
-fn input() -> String {
-    std::fs::read_to_string("input/input_08.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_08.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
+
+    let (directions, rule) = view_settings(params);
+    maybe_export(params, &contents, directions, rule);
 
-    let val = a_with_input(&contents);
+    let val = a_with_input(&contents, directions, rule);
 
     val.to_string()
 }
 
-fn a_with_input(input: &str) -> usize {
-    let height_grid: Grid<i32> = parse_tree_map(input);
-
-    let num_rows = height_grid.num_rows;
-    let num_cols = height_grid.num_cols;
-
-    let mut seen_grid: Grid<bool> = Grid::new(vec![false; num_rows * num_cols], num_rows, num_cols);
-
-    fn maybe_tag_seen(
-        running_max: &mut i32,
-        height_grid: &Grid<i32>,
-        seen_grid: &mut Grid<bool>,
-        col: usize,
-        row: usize,
-    ) {
-        let height = height_grid.get(col, row);
-        if height > *running_max {
-            *running_max = height;
-            seen_grid.set(col, row, true);
-        }
-    }
-
-    // it hurts me that i wrote out each direction manually
-    // but i feel lazy about making more reusable code? it only took like 10 minutes :thinking:
+// by default, the puzzle's rules: look along the rows and columns, and any tree at least as tall
+// as you blocks your view
+fn view_settings(params: &Params) -> (&'static [Direction], ViewRule) {
+    let directions = if params.get("diagonals", false) {
+        &Direction::ALL[..]
+    } else {
+        &Direction::ORTHOGONAL[..]
+    };
+
+    let blocked_at: String = params.get("blocked_at", String::new());
+    let rule = if blocked_at.is_empty() {
+        ViewRule::AtLeastOwnHeight
+    } else {
+        ViewRule::AtLeast(blocked_at.parse().expect("blocked_at should be a height"))
+    };
+
+    (directions, rule)
+}
 
-    // sweep left to right in each row
-    for row in 0..num_rows {
-        seen_grid.set(0, row, true);
-        let mut running_max = height_grid.get(0, row);
-        for col in 1..num_cols {
-            maybe_tag_seen(&mut running_max, &height_grid, &mut seen_grid, col, row);
-        }
+// with --param export=some/dir, writes the visibility mask and scenic scores out as images (to
+// look at) and CSVs (to poke at)
+fn maybe_export(params: &Params, input: &str, directions: &[Direction], rule: ViewRule) {
+    let dir: String = params.get("export", String::new());
+    if dir.is_empty() {
+        return;
     }
 
-    // sweep right to left in each row
-    for row in 0..num_rows {
-        seen_grid.set(num_cols - 1, row, true);
-        let mut running_max = height_grid.get(num_cols - 1, row);
-        for col in (0..num_cols - 1).rev() {
-            maybe_tag_seen(&mut running_max, &height_grid, &mut seen_grid, col, row);
-        }
+    let height_grid = parse_tree_map(input);
+    let seen = visibility(&height_grid, directions, rule);
+    let scores = scenic_scores(&height_grid, directions, rule);
+
+    let files = [
+        ("visibility.pgm", mask_to_pgm(&seen)),
+        ("visibility.csv", seen.map(|b| b as u8).to_csv()),
+        ("scenic.ppm", heatmap_to_ppm(&scores)),
+        ("scenic.csv", scores.to_csv()),
+    ];
+
+    std::fs::create_dir_all(&dir).expect("Should be able to create the export directory");
+    for (name, contents) in files {
+        let path = std::path::Path::new(&dir).join(name);
+        std::fs::write(&path, contents)
+            .unwrap_or_else(|e| panic!("Could not write {}: {}", path.display(), e));
     }
+}
 
-    // sweep top to bottom in each column
-    for col in 0..num_cols {
-        seen_grid.set(col, 0, true);
-        let mut running_max = height_grid.get(col, 0);
-        for row in 1..num_rows {
-            maybe_tag_seen(&mut running_max, &height_grid, &mut seen_grid, col, row);
-        }
-    }
+fn a_with_input(input: &str, directions: &[Direction], rule: ViewRule) -> usize {
+    let height_grid: Grid<i32> = parse_tree_map(input);
 
-    // sweep bottom to top in each column
-    for col in 0..num_cols {
-        seen_grid.set(col, num_rows - 1, true);
-        let mut running_max = height_grid.get(col, num_rows - 1);
-        for row in (0..num_rows - 1).rev() {
-            maybe_tag_seen(&mut running_max, &height_grid, &mut seen_grid, col, row);
-        }
-    }
+    let seen_grid = visibility(&height_grid, directions, rule);
 
     seen_grid.data.iter().copied().filter(|b| *b).count()
 }
@@ -107,89 +103,273 @@ impl<T: Copy> Grid<T> {
     }
 }
 
+impl<T: Copy> Grid<T> {
+    fn map<U, F: Fn(T) -> U>(&self, f: F) -> Grid<U> {
+        Grid::new(
+            self.data.iter().copied().map(f).collect(),
+            self.num_rows,
+            self.num_cols,
+        )
+    }
+}
+
+impl<T: std::fmt::Display> Grid<T> {
+    // one line per row, comma separated
+    fn to_csv(&self) -> String {
+        let mut out = String::new();
+        for row in self.data.chunks(self.num_cols) {
+            let cells: Vec<String> = row.iter().map(|val| val.to_string()).collect();
+            out.push_str(&cells.join(","));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+// Plain (ASCII) PGM, so no image library needed: visible trees are white, hidden ones black
+fn mask_to_pgm(mask: &Grid<bool>) -> String {
+    let mut out = format!("P2\n{} {}\n1\n", mask.num_cols, mask.num_rows);
+    for row in mask.data.chunks(mask.num_cols) {
+        let cells: Vec<&str> = row.iter().map(|b| if *b { "1" } else { "0" }).collect();
+        out.push_str(&cells.join(" "));
+        out.push('\n');
+    }
+    out
+}
+
+// Plain (ASCII) PPM, scaled so the best score is white and zero is black, going through red and
+// yellow on the way (the usual "hot" colormap)
+fn heatmap_to_ppm(values: &Grid<usize>) -> String {
+    let max = values.data.iter().copied().max().unwrap_or(0).max(1);
+
+    let mut out = format!("P3\n{} {}\n255\n", values.num_cols, values.num_rows);
+    for row in values.data.chunks(values.num_cols) {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|val| {
+                // 0 ..= 765, split evenly between red, green and blue ramping up
+                let level = (*val as f64 / max as f64 * 765.0).round() as usize;
+                let r = level.min(255);
+                let g = level.saturating_sub(255).min(255);
+                let b = level.saturating_sub(510).min(255);
+                format!("{} {} {}", r, g, b)
+            })
+            .collect();
+        out.push_str(&cells.join("  "));
+        out.push('\n');
+    }
+    out
+}
+
 fn char_to_int(c: char) -> i32 {
     return ((c as usize) - ('0' as usize)) as i32;
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
+
+    let (directions, rule) = view_settings(params);
+    maybe_export(params, &contents, directions, rule);
 
-    let val = b_with_input(&contents);
+    let val = b_with_input(&contents, directions, rule);
 
     val.to_string()
 }
 
-fn b_with_input(input: &str) -> usize {
+fn b_with_input(input: &str, directions: &[Direction], rule: ViewRule) -> usize {
     let height_grid = parse_tree_map(input);
 
-    let mut best = 0;
+    let scores = scenic_scores(&height_grid, directions, rule);
 
-    // O(n^3) where n is the side of the grid (assuming grid is nearly square)
-    for row in 1..height_grid.num_rows - 1 {
-        for col in 1..height_grid.num_cols - 1 {
-            let score = scenic_score(&height_grid, col, row);
-            best = best.max(score);
-        }
-    }
-
-    best
+    scores.data.iter().copied().max().unwrap_or(0)
 }
 
-// O(n) where n is the side of the grid (assuming grid is nearly square)
-fn scenic_score(height_map: &Grid<i32>, center_col: usize, center_row: usize) -> usize {
-    let left_view = left_view(&height_map, center_col, center_row);
-    let right_view = right_view(&height_map, center_col, center_row);
-    let up_view = up_view(&height_map, center_col, center_row);
-    let down_view = down_view(&height_map, center_col, center_row);
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct Direction {
+    d_col: isize,
+    d_row: isize,
+}
 
-    left_view * right_view * up_view * down_view
+impl Direction {
+    const LEFT: Direction = Direction {
+        d_col: -1,
+        d_row: 0,
+    };
+    const RIGHT: Direction = Direction { d_col: 1, d_row: 0 };
+    const UP: Direction = Direction {
+        d_col: 0,
+        d_row: -1,
+    };
+    const DOWN: Direction = Direction { d_col: 0, d_row: 1 };
+
+    const ORTHOGONAL: [Direction; 4] = [
+        Direction::LEFT,
+        Direction::RIGHT,
+        Direction::UP,
+        Direction::DOWN,
+    ];
+
+    const ALL: [Direction; 8] = [
+        Direction::LEFT,
+        Direction::RIGHT,
+        Direction::UP,
+        Direction::DOWN,
+        Direction {
+            d_col: -1,
+            d_row: -1,
+        },
+        Direction {
+            d_col: 1,
+            d_row: -1,
+        },
+        Direction {
+            d_col: -1,
+            d_row: 1,
+        },
+        Direction { d_col: 1, d_row: 1 },
+    ];
 }
 
-// yes, i split this into its own function to play "find the typo" with unit tests
-fn left_view(height_map: &Grid<i32>, center_col: usize, center_row: usize) -> usize {
-    let starting_height = height_map.get(center_col, center_row);
+// Which trees get in the way of the view from a tree
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum ViewRule {
+    // the puzzle's rule: anything at least as tall as the tree we're standing in
+    AtLeastOwnHeight,
+    // anything at least this tall, however tall our own tree is
+    AtLeast(i32),
+}
 
-    let last_left = (0..center_col)
-        .rev()
-        .take_while(|col| height_map.get(*col, center_row) < starting_height)
-        .last()
-        .unwrap_or(center_col);
-    (center_col - last_left + 1).min(center_col)
+// What a tree sees when it looks in one direction
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+struct View {
+    // how many trees it can see, including the one blocking the view (if any)
+    distance: usize,
+    // whether nothing blocks the view, so the tree is visible from outside the grid this way
+    to_edge: bool,
 }
 
-// yes, i split this into its own function to play "find the typo" with unit tests
-fn right_view(height_map: &Grid<i32>, center_col: usize, center_row: usize) -> usize {
-    let starting_height = height_map.get(center_col, center_row);
-    let right_edge = height_map.num_cols - 1;
+// What each tree sees looking in the given direction.
+//
+// We walk each line of trees back from the edge it's looking towards. For the puzzle's rule we
+// keep a stack of the trees we've passed which could still block a view: anything behind a tree
+// at least as tall is hidden by it, so heights strictly decrease going up the stack. A tree pops
+// everything shorter than itself, and whatever is left on top is its blocker. Each tree is pushed
+// and popped at most once per line, so this is O(rows * cols) overall. With a fixed threshold
+// it's simpler still: the blocker is just the last tree we passed that was tall enough.
+fn views(height_map: &Grid<i32>, dir: Direction, rule: ViewRule) -> Grid<View> {
+    let num_rows = height_map.num_rows;
+    let num_cols = height_map.num_cols;
+
+    let mut out: Grid<View> = Grid::new(
+        vec![View::default(); num_rows * num_cols],
+        num_rows,
+        num_cols,
+    );
+
+    let in_bounds = |col: isize, row: isize| {
+        col >= 0 && row >= 0 && (col as usize) < num_cols && (row as usize) < num_rows
+    };
+
+    // (steps from the edge, height)
+    let mut stack: Vec<(usize, i32)> = Vec::new();
+
+    for start_row in 0..num_rows as isize {
+        for start_col in 0..num_cols as isize {
+            // each line starts at a tree with nothing further along in the direction we look
+            if in_bounds(start_col + dir.d_col, start_row + dir.d_row) {
+                continue;
+            }
+
+            stack.clear();
+            // steps from the edge of the last tree at least as tall as a fixed threshold
+            let mut last_tall: Option<usize> = None;
+            let (mut col, mut row) = (start_col, start_row);
+            let mut steps = 0;
+
+            while in_bounds(col, row) {
+                let height = height_map.get(col as usize, row as usize);
+
+                let blocker = match rule {
+                    ViewRule::AtLeastOwnHeight => {
+                        while stack.last().is_some_and(|(_, h)| *h < height) {
+                            stack.pop();
+                        }
+                        let blocker = stack.last().map(|(s, _)| *s);
+
+                        // a tree of the same height hides this one's blocker from anything
+                        // further along too
+                        if stack.last().is_some_and(|(_, h)| *h == height) {
+                            stack.pop();
+                        }
+                        stack.push((steps, height));
+
+                        blocker
+                    }
+                    ViewRule::AtLeast(threshold) => {
+                        let blocker = last_tall;
+                        if height >= threshold {
+                            last_tall = Some(steps);
+                        }
+                        blocker
+                    }
+                };
+
+                let view = match blocker {
+                    None => View {
+                        distance: steps,
+                        to_edge: true,
+                    },
+                    Some(blocker_steps) => View {
+                        distance: steps - blocker_steps,
+                        to_edge: false,
+                    },
+                };
+                out.set(col as usize, row as usize, view);
+
+                col -= dir.d_col;
+                row -= dir.d_row;
+                steps += 1;
+            }
+        }
+    }
 
-    let last_right = (center_col + 1..height_map.num_cols)
-        .take_while(|col| height_map.get(*col, center_row) < starting_height)
-        .last()
-        .unwrap_or(center_col);
-    (last_right + 1 - center_col).min(right_edge - center_col)
+    out
 }
 
-// this one worked first try but the code felt asymmetric without making it a function too
-fn up_view(height_map: &Grid<i32>, center_col: usize, center_row: usize) -> usize {
-    let starting_height = height_map.get(center_col, center_row);
+// whether each tree can be seen from outside the grid along any of the directions
+fn visibility(height_map: &Grid<i32>, directions: &[Direction], rule: ViewRule) -> Grid<bool> {
+    let mut out = Grid::new(
+        vec![false; height_map.data.len()],
+        height_map.num_rows,
+        height_map.num_cols,
+    );
+
+    for dir in directions.iter().copied() {
+        let views = views(height_map, dir, rule);
+        for (seen, view) in out.data.iter_mut().zip(views.data.iter()) {
+            *seen |= view.to_edge;
+        }
+    }
 
-    let last_up = (0..center_row)
-        .rev()
-        .take_while(|row| height_map.get(center_col, *row) < starting_height)
-        .last()
-        .unwrap_or(center_row);
-    (center_row - last_up + 1).min(center_row)
+    out
 }
 
-fn down_view(height_map: &Grid<i32>, center_col: usize, center_row: usize) -> usize {
-    let starting_height = height_map.get(center_col, center_row);
-    let bottom_edge = height_map.num_rows - 1;
+// the product of the viewing distances along each of the directions
+fn scenic_scores(height_map: &Grid<i32>, directions: &[Direction], rule: ViewRule) -> Grid<usize> {
+    let mut out = Grid::new(
+        vec![1; height_map.data.len()],
+        height_map.num_rows,
+        height_map.num_cols,
+    );
+
+    for dir in directions.iter().copied() {
+        let views = views(height_map, dir, rule);
+        for (score, view) in out.data.iter_mut().zip(views.data.iter()) {
+            *score *= view.distance;
+        }
+    }
 
-    let last_down = (center_row + 1..height_map.num_rows)
-        .take_while(|row| height_map.get(center_col, *row) < starting_height)
-        .last()
-        .unwrap_or(center_row);
-    (last_down + 1 - center_row).min(bottom_edge - center_row)
+    out
 }
 
 fn parse_tree_map(input: &str) -> Grid<i32> {
@@ -220,31 +400,106 @@ mod tests {
 
     #[test]
     fn sample_a() {
-        assert_eq!(a_with_input(SAMPLE_INPUT_STR), 21);
+        assert_eq!(
+            a_with_input(
+                SAMPLE_INPUT_STR,
+                &Direction::ORTHOGONAL,
+                ViewRule::AtLeastOwnHeight
+            ),
+            21
+        );
     }
 
     #[test]
     fn sample_b() {
-        assert_eq!(b_with_input(SAMPLE_INPUT_STR), 8);
+        assert_eq!(
+            b_with_input(
+                SAMPLE_INPUT_STR,
+                &Direction::ORTHOGONAL,
+                ViewRule::AtLeastOwnHeight
+            ),
+            8
+        );
     }
 
     #[test]
     fn scenic_view_test() {
         let grid = parse_tree_map(SAMPLE_INPUT_STR);
+        let rule = ViewRule::AtLeastOwnHeight;
+
+        let left = views(&grid, Direction::LEFT, rule);
+        let left_view = |col, row| left.get(col, row).distance;
+        assert_eq!(left_view(0, 1), 0);
+        assert_eq!(left_view(1, 1), 1);
+        assert_eq!(left_view(2, 1), 1);
+        assert_eq!(left_view(3, 1), 1);
+        assert_eq!(left_view(4, 1), 2);
+
+        let right = views(&grid, Direction::RIGHT, rule);
+        let right_view = |col, row| right.get(col, row).distance;
+        assert_eq!(right_view(0, 1), 1);
+        assert_eq!(right_view(1, 1), 1);
+        assert_eq!(right_view(2, 1), 2);
+        assert_eq!(right_view(3, 1), 1);
+        assert_eq!(right_view(4, 1), 0);
+
+        let scores = scenic_scores(&grid, &Direction::ORTHOGONAL, rule);
+        assert_eq!(scores.get(2, 1), 4);
+        assert_eq!(scores.get(2, 3), 8);
+    }
 
-        assert_eq!(left_view(&grid, 0, 1), 0);
-        assert_eq!(left_view(&grid, 1, 1), 1);
-        assert_eq!(left_view(&grid, 2, 1), 1);
-        assert_eq!(left_view(&grid, 3, 1), 1);
-        assert_eq!(left_view(&grid, 4, 1), 2);
-
-        assert_eq!(right_view(&grid, 0, 1), 1);
-        assert_eq!(right_view(&grid, 1, 1), 1);
-        assert_eq!(right_view(&grid, 2, 1), 2);
-        assert_eq!(right_view(&grid, 3, 1), 1);
-        assert_eq!(right_view(&grid, 4, 1), 0);
+    #[test]
+    fn other_rules() {
+        let grid = parse_tree_map(SAMPLE_INPUT_STR);
 
-        assert_eq!(scenic_score(&grid, 2, 1), 4);
+        // the 5 in the middle of the fourth row looks up-left straight at another 5; the 4 next
+        // to it looks up-right over a 2 and out of the forest
+        let up_left = views(&grid, Direction::ALL[4], ViewRule::AtLeastOwnHeight);
+        assert_eq!(
+            up_left.get(2, 3),
+            View {
+                distance: 1,
+                to_edge: false
+            }
+        );
+        let up_right = views(&grid, Direction::ALL[5], ViewRule::AtLeastOwnHeight);
+        assert_eq!(
+            up_right.get(3, 3),
+            View {
+                distance: 1,
+                to_edge: true
+            }
+        );
+
+        // which makes that 4 the only interior tree the diagonals reveal
+        assert_eq!(
+            a_with_input(
+                SAMPLE_INPUT_STR,
+                &Direction::ALL,
+                ViewRule::AtLeastOwnHeight
+            ),
+            22
+        );
+
+        // if only trees of height 9 block anything, the 9s see right out to the edge, but cut
+        // short the views of the trees behind them
+        let left = views(&grid, Direction::LEFT, ViewRule::AtLeast(9));
+        assert!(left.get(3, 4).to_edge);
+        assert_eq!(
+            left.get(4, 4),
+            View {
+                distance: 1,
+                to_edge: false
+            }
+        );
+        assert_eq!(
+            a_with_input(
+                SAMPLE_INPUT_STR,
+                &Direction::ORTHOGONAL,
+                ViewRule::AtLeast(10)
+            ),
+            25
+        );
     }
 
     #[test]
@@ -260,4 +515,28 @@ mod tests {
         assert_eq!(char_to_int('8'), 8);
         assert_eq!(char_to_int('9'), 9);
     }
+
+    #[test]
+    fn exports() {
+        let grid = parse_tree_map("303\n255\n653");
+        let rule = ViewRule::AtLeastOwnHeight;
+
+        let seen = visibility(&grid, &Direction::ORTHOGONAL, rule);
+        assert_eq!(mask_to_pgm(&seen), "P2\n3 3\n1\n1 1 1\n1 1 1\n1 1 1\n");
+        assert_eq!(seen.map(|b| b as u8).to_csv(), "1,1,1\n1,1,1\n1,1,1\n");
+
+        let scores = scenic_scores(&grid, &Direction::ORTHOGONAL, rule);
+        assert_eq!(scores.to_csv(), "0,0,0\n0,1,0\n0,0,0\n");
+        assert_eq!(
+            heatmap_to_ppm(&scores),
+            "P3\n3 3\n255\n0 0 0  0 0 0  0 0 0\n0 0 0  255 255 255  0 0 0\n0 0 0  0 0 0  0 0 0\n"
+        );
+
+        // something in the middle of the range is a shade of red
+        let half = Grid::new(vec![0, 1, 6], 1, 3);
+        assert_eq!(
+            heatmap_to_ppm(&half),
+            "P3\n3 1\n255\n0 0 0  128 0 0  255 255 255\n"
+        );
+    }
 }
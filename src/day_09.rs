@@ -1,97 +1,249 @@
 use std::collections::HashSet;
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_09.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_09.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
 
     let val = a_with_input(&contents);
 
+    if params.get("show", false) {
+        show(&contents, 2);
+    }
+
     val.to_string()
 }
 
 fn a_with_input(input: &str) -> usize {
-    move_with_input::<2>(input)
+    simulate(input, 2).visited(1).len()
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let num_knots = params.get("knots", 10);
+    if num_knots < 1 {
+        return format!(
+            "A rope needs at least one knot (the head), not {}",
+            num_knots
+        );
+    }
+
+    let contents = input(params);
+
+    let val = b_with_input(&contents, num_knots);
 
-    let val = b_with_input(&contents);
+    if params.get("show", false) {
+        show(&contents, num_knots);
+    }
 
     val.to_string()
 }
 
-fn b_with_input(input: &str) -> usize {
-    move_with_input::<10>(input)
+fn b_with_input(input: &str, num_knots: usize) -> usize {
+    simulate(input, num_knots).visited(num_knots - 1).len()
 }
 
-fn move_with_input<const LENGTH: usize>(input: &str) -> usize {
-    let mut seen: HashSet<(i32, i32)> = HashSet::new();
-    let mut positions: [(i32, i32); LENGTH] = [(0, 0); LENGTH];
+fn simulate(input: &str, num_knots: usize) -> Rope {
+    let mut rope = Rope::new(num_knots);
 
-    seen.insert(positions[LENGTH - 1]);
+    for m in input.lines().map(parse_move) {
+        rope.apply(m);
+    }
 
-    fn far(a: (i32, i32), b: (i32, i32)) -> bool {
-        (a.0 - b.0).abs() > 1 || (a.1 - b.1).abs() > 1
+    rope
+}
+
+// prints the rope after every move, like the puzzle's worked examples, then a summary of what
+// each knot did
+fn show(input: &str, num_knots: usize) {
+    let moves: Vec<Move> = input.lines().map(parse_move).collect();
+    let rope = simulate(input, num_knots);
+    let bounds = rope.bounds();
+
+    let mut time = 0;
+    for (line, m) in input.lines().zip(moves) {
+        time += m.amt as usize;
+        println!("== {} ==\n\n{}\n", line, rope.render(time, bounds));
     }
 
-    for Move { dir, amt } in input.lines().map(|line| parse_move(line)) {
-        for _ in 0..amt {
-            match dir {
-                Dir::D => positions[0].1 -= 1,
-                Dir::U => positions[0].1 += 1,
-                Dir::L => positions[0].0 -= 1,
-                Dir::R => positions[0].0 += 1,
-            };
+    for knot in 0..num_knots {
+        let first_move = match rope.first_move(knot) {
+            Some(t) => format!("first moves at step {}", t),
+            None => "never moves".to_string(),
+        };
+        println!(
+            "knot {}: visits {} positions, {}",
+            knot,
+            rope.visited(knot).len(),
+            first_move
+        );
+    }
+}
+
+// (min x, max x, min y, max y), inclusive
+type Bounds = (i32, i32, i32, i32);
 
-            for i in 0..LENGTH - 1 {
-                let head_pos = positions[i];
-                let tail_pos = &mut positions[i + 1];
-                while far(head_pos, *tail_pos) {
-                    tail_pos.0 += (head_pos.0 - tail_pos.0).signum();
-                    tail_pos.1 += (head_pos.1 - tail_pos.1).signum();
+// A rope whose knots all start at (0, 0), with a record of where every knot was after every
+// step. Time 0 is the start; each step moves the head one square.
+struct Rope {
+    // trajectories[k][t] is where knot k was at time t
+    trajectories: Vec<Vec<(i32, i32)>>,
+}
 
-                    if i + 1 == LENGTH - 1 {
-                        seen.insert(*tail_pos);
-                    }
-                }
+impl Rope {
+    fn new(num_knots: usize) -> Rope {
+        assert!(num_knots > 0, "Rope needs at least a head");
+
+        Rope {
+            trajectories: vec![vec![(0, 0)]; num_knots],
+        }
+    }
+
+    fn num_knots(&self) -> usize {
+        self.trajectories.len()
+    }
+
+    fn apply(&mut self, m: Move) {
+        for _ in 0..m.amt {
+            self.step(m.dir);
+        }
+    }
+
+    fn step(&mut self, dir: Dir) {
+        let (dx, dy) = dir.delta();
+        let (hx, hy) = *self.trajectories[0].last().unwrap();
+        let mut leader = (hx + dx, hy + dy);
+        self.trajectories[0].push(leader);
+
+        // each knot only has to catch up with the one in front; even if that one moved
+        // diagonally, a single step (diagonal if need be) is always enough
+        for trajectory in self.trajectories.iter_mut().skip(1) {
+            let mut pos = *trajectory.last().unwrap();
+            if (leader.0 - pos.0).abs() > 1 || (leader.1 - pos.1).abs() > 1 {
+                pos.0 += (leader.0 - pos.0).signum();
+                pos.1 += (leader.1 - pos.1).signum();
             }
+            trajectory.push(pos);
+            leader = pos;
         }
     }
 
-    seen.len()
+    fn trajectory(&self, knot: usize) -> &[(i32, i32)] {
+        &self.trajectories[knot]
+    }
+
+    fn visited(&self, knot: usize) -> HashSet<(i32, i32)> {
+        self.trajectory(knot).iter().copied().collect()
+    }
+
+    // the first time at which the knot isn't where it was the step before
+    fn first_move(&self, knot: usize) -> Option<usize> {
+        self.trajectory(knot)
+            .windows(2)
+            .position(|w| w[0] != w[1])
+            .map(|idx| idx + 1)
+    }
+
+    // the smallest box holding everywhere any knot has been
+    fn bounds(&self) -> Bounds {
+        let all = self.trajectories.iter().flatten();
+        (
+            all.clone().map(|p| p.0).min().unwrap(),
+            all.clone().map(|p| p.0).max().unwrap(),
+            all.clone().map(|p| p.1).min().unwrap(),
+            all.map(|p| p.1).max().unwrap(),
+        )
+    }
+
+    // The puzzle's picture of the rope at the given time: H for the head, then 1, 2, ... for the
+    // other knots (or T, if there's only a tail), s for the start, and . for everywhere else.
+    // Past 9 the knots go on a, b, ..., z; there are no letters left after knot 35, so the rest
+    // are all drawn as #. Where knots overlap, the one nearer the head is drawn. Up is positive y.
+    fn render(&self, time: usize, bounds: Bounds) -> String {
+        let (x_min, x_max, y_min, y_max) = bounds;
+
+        let label = |knot: usize| -> char {
+            if knot == 0 {
+                'H'
+            } else if self.num_knots() == 2 {
+                'T'
+            } else {
+                std::char::from_digit(knot as u32, 36).unwrap_or('#')
+            }
+        };
+
+        let mut lines = Vec::new();
+        for y in (y_min..=y_max).rev() {
+            let mut line = String::new();
+            for x in x_min..=x_max {
+                let knot = (0..self.num_knots()).find(|&k| self.trajectories[k][time] == (x, y));
+                line.push(match knot {
+                    Some(k) => label(k),
+                    None if (x, y) == (0, 0) => 's',
+                    None => '.',
+                });
+            }
+            lines.push(line);
+        }
+
+        lines.join("\n")
+    }
 }
 
 fn parse_move(line: &str) -> Move {
-    let dir = parse_dir(line.chars().next().unwrap());
     // look, no error handling, it's a puzzle not enterprise software
-    let amt_str = line.chars().skip(2).collect::<String>();
+    let (dir_str, amt_str) = line.split_once(' ').unwrap();
+    let dir = parse_dir(dir_str);
     let amt = amt_str.parse::<i32>().unwrap();
     Move { dir, amt }
 }
 
-fn parse_dir(c: char) -> Dir {
-    match c {
-        'R' => Dir::R,
-        'U' => Dir::U,
-        'L' => Dir::L,
-        'D' => Dir::D,
+fn parse_dir(s: &str) -> Dir {
+    match s {
+        "R" => Dir::R,
+        "U" => Dir::U,
+        "L" => Dir::L,
+        "D" => Dir::D,
+        "UR" | "RU" => Dir::UR,
+        "UL" | "LU" => Dir::UL,
+        "DR" | "RD" => Dir::DR,
+        "DL" | "LD" => Dir::DL,
         other => unimplemented!("Bad direction input: {}", other),
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 enum Dir {
     R,
     U,
     L,
     D,
+    UR,
+    UL,
+    DR,
+    DL,
+}
+
+impl Dir {
+    fn delta(self) -> (i32, i32) {
+        match self {
+            Dir::R => (1, 0),
+            Dir::U => (0, 1),
+            Dir::L => (-1, 0),
+            Dir::D => (0, -1),
+            Dir::UR => (1, 1),
+            Dir::UL => (-1, 1),
+            Dir::DR => (1, -1),
+            Dir::DL => (-1, -1),
+        }
+    }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 struct Move {
     dir: Dir,
     amt: i32,
@@ -126,7 +278,69 @@ U 20";
 
     #[test]
     fn sample_b() {
-        assert_eq!(b_with_input(SAMPLE_INPUT_STR), 1);
-        assert_eq!(b_with_input(SECOND_SAMPLE), 36);
+        assert_eq!(b_with_input(SAMPLE_INPUT_STR, 10), 1);
+        assert_eq!(b_with_input(SECOND_SAMPLE, 10), 36);
+    }
+
+    #[test]
+    fn frames() {
+        let rope = simulate(SAMPLE_INPUT_STR, 2);
+        let bounds = (0, 5, 0, 4);
+
+        // after U 4
+        assert_eq!(
+            rope.render(8, bounds),
+            "....H.\n....T.\n......\n......\ns....."
+        );
+        // and at the very end
+        assert_eq!(
+            rope.render(24, bounds),
+            "......\n......\n.TH...\n......\ns....."
+        );
+
+        let rope = simulate(SAMPLE_INPUT_STR, 10);
+        // after R 4 then U 4; knot 5 has taken one diagonal step, and 6 through 9 are still at
+        // the start, where 6 covers 7-9 and s
+        assert_eq!(
+            rope.render(8, bounds),
+            "....H.\n....1.\n..432.\n.5....\n6....."
+        );
+
+        // pulled out straight, so every knot shows; past z they're all #
+        let rope = simulate("R 40", 40);
+        assert_eq!(
+            rope.render(40, (0, 40, 0, 0)),
+            "s####zyxwvutsrqponmlkjihgfedcba987654321H"
+        );
+    }
+
+    #[test]
+    fn no_knots() {
+        let params =
+            Params::from_args(["--param", "knots=0"].map(String::from).into_iter()).unwrap();
+        assert_eq!(
+            b(&params),
+            "A rope needs at least one knot (the head), not 0"
+        );
+    }
+
+    #[test]
+    fn knot_queries() {
+        let rope = simulate(SAMPLE_INPUT_STR, 10);
+
+        assert_eq!(rope.first_move(0), Some(1));
+        // knot 1 doesn't move until the head is two away
+        assert_eq!(rope.first_move(1), Some(2));
+        assert_eq!(rope.first_move(9), None);
+        assert_eq!(rope.visited(9).len(), 1);
+        assert_eq!(rope.trajectory(0).len(), 25);
+
+        // diagonal head moves drag the tail along diagonally too
+        let rope = simulate("UR 3\nDL 1", 2);
+        assert_eq!(
+            rope.trajectory(1),
+            &[(0, 0), (0, 0), (1, 1), (2, 2), (2, 2)]
+        );
+        assert_eq!(rope.trajectory(0).last(), Some(&(2, 2)));
     }
 }
@@ -1,114 +1,394 @@
-use std::collections::VecDeque;
+use crate::normalize::Whitespace;
+use crate::ocr;
+use crate::params::Params;
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_10.txt").expect("Should be able to read the file")
+fn input(params: &Params) -> String {
+    params.read_input("input/input_10.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
 
-    let val = a_with_input(&contents);
+    let milestones = params.get_list("milestones", &[20, 60, 100, 140, 180, 220]);
+
+    let val = a_with_input(&contents, &milestones);
 
     val.to_string()
 }
 
-fn a_with_input(input: &str) -> i64 {
-    let mut x_val: i64 = 1;
-    let mut timer: usize = 0;
-
-    let milestones: Vec<usize> = vec![20, 60, 100, 140, 180, 220];
-    let mut milestone_idx: usize = 0;
-    let mut running_score: i64 = 0;
-
-    for line in input.lines() {
-        let old_val = x_val;
-        if line == "noop" {
-            timer += 1;
-        } else if line.starts_with("addx") {
-            timer += 2;
-            let diff = line
-                .chars()
-                .skip(5)
-                .collect::<String>()
-                .parse::<i64>()
-                .unwrap();
-            x_val += diff;
-        } else {
-            unimplemented!("Unknown input: {}", line);
+fn a_with_input(input: &str, milestones: &[usize]) -> i64 {
+    let program = parse::parse_input(input);
+
+    let mut probe = SignalProbe::new(milestones);
+    Cpu::new(program).run(&mut [&mut probe]);
+
+    probe.total
+}
+
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
+
+    // with --param assemble=true, the input is a picture, and we write a program which draws it
+    if params.get("assemble", false) {
+        return match assemble(&contents, 40, 6) {
+            Ok(program) => format!(
+                "\n{}",
+                program
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            Err(e) => e,
+        };
+    }
+
+    // with --param annotate=true, show what X is during every cycle of the program
+    if params.get("annotate", false) {
+        return format!("\n{}", annotate(&parse::parse_input(&contents)));
+    }
+
+    let picture = b_with_input(&contents);
+
+    // with --param raw=true, just show the picture
+    if params.get("raw", false) {
+        return picture;
+    }
+
+    match ocr::read_letters(&picture) {
+        Ok(letters) => letters,
+        // still show the picture, so a human can have a go at it
+        Err(e) => format!("{}\n\n{}", picture, e),
+    }
+}
+
+fn b_with_input(input: &str) -> String {
+    let program = parse::parse_input(input);
+
+    let mut crt = Crt::new(40, 6);
+    Cpu::new(program).run(&mut [&mut crt]);
+
+    crt.render()
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Instruction {
+    Noop,
+    Addx(i64),
+}
+
+impl Instruction {
+    // how many cycles the instruction takes to finish; its effect lands at the end of the last one
+    fn cycles(self) -> usize {
+        match self {
+            Instruction::Noop => 1,
+            Instruction::Addx(_) => 2,
         }
+    }
 
-        if milestone_idx >= milestones.len() {
-            break;
+    fn execute(self, registers: &mut Registers) {
+        match self {
+            Instruction::Noop => {}
+            Instruction::Addx(diff) => registers.x += diff,
         }
+    }
+}
 
-        let milestone = milestones[milestone_idx];
-        if milestone <= timer {
-            running_score += milestone as i64 * old_val;
-            milestone_idx += 1;
+// the same syntax as the input
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Noop => write!(f, "noop"),
+            Instruction::Addx(diff) => write!(f, "addx {}", diff),
         }
     }
+}
 
-    running_score
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct Registers {
+    x: i64,
 }
 
-pub fn b() -> String {
-    let contents = input();
+// Something hooked up to the CPU which sees the registers during every cycle
+trait Peripheral {
+    // cycles are 1-indexed, like the puzzle
+    fn tick(&mut self, cycle: usize, registers: &Registers);
+}
 
-    let val = b_with_input(&contents);
+struct Cpu {
+    program: Vec<Instruction>,
+    // the instruction being executed, and how many of its cycles are already done
+    pc: usize,
+    cycles_into_instruction: usize,
+    // the number of cycles completed so far
+    cycle: usize,
+    registers: Registers,
+}
 
-    val.to_string()
+impl Cpu {
+    fn new(program: Vec<Instruction>) -> Cpu {
+        Cpu {
+            program,
+            pc: 0,
+            cycles_into_instruction: 0,
+            cycle: 0,
+            registers: Registers { x: 1 },
+        }
+    }
+
+    // Runs one cycle, returning its number along with the registers as they were *during* the
+    // cycle (so before any instruction finishing on this cycle takes effect), or None if the
+    // program has finished.
+    fn step(&mut self) -> Option<(usize, Registers)> {
+        let instruction = *self.program.get(self.pc)?;
+
+        self.cycle += 1;
+        let during = self.registers;
+
+        self.cycles_into_instruction += 1;
+        if self.cycles_into_instruction == instruction.cycles() {
+            instruction.execute(&mut self.registers);
+            self.pc += 1;
+            self.cycles_into_instruction = 0;
+        }
+
+        Some((self.cycle, during))
+    }
+
+    // runs to the end of the program, showing every cycle to every peripheral
+    fn run(mut self, peripherals: &mut [&mut dyn Peripheral]) {
+        while let Some((cycle, registers)) = self.step() {
+            for peripheral in peripherals.iter_mut() {
+                peripheral.tick(cycle, &registers);
+            }
+        }
+    }
 }
 
-fn b_with_input(input: &str) -> String {
-    // a move is (END_TIME, DIFF)
-    let mut moves: VecDeque<(i64, i64)> = VecDeque::new();
-
-    let mut parsing_timer: i64 = 0;
-    for line in input.lines() {
-        if line == "noop" {
-            parsing_timer += 1;
-        } else if line.starts_with("addx") {
-            parsing_timer += 2;
-            let diff = line
-                .chars()
-                .skip(5)
-                .collect::<String>()
-                .parse::<i64>()
-                .unwrap();
-            moves.push_back((parsing_timer, diff));
-        } else {
-            unimplemented!("Unknown input: {}", line);
+// (cycle, X during that cycle)
+impl Iterator for Cpu {
+    type Item = (usize, i64);
+
+    fn next(&mut self) -> Option<(usize, i64)> {
+        self.step().map(|(cycle, registers)| (cycle, registers.x))
+    }
+}
+
+// Adds up the signal strength (cycle number times X) at each of the milestone cycles
+struct SignalProbe {
+    milestones: Vec<usize>,
+    total: i64,
+}
+
+impl SignalProbe {
+    fn new(milestones: &[usize]) -> SignalProbe {
+        SignalProbe {
+            milestones: milestones.to_vec(),
+            total: 0,
         }
     }
+}
 
-    let mut x_val: i64 = 1;
+impl Peripheral for SignalProbe {
+    fn tick(&mut self, cycle: usize, registers: &Registers) {
+        if self.milestones.contains(&cycle) {
+            self.total += cycle as i64 * registers.x;
+        }
+    }
+}
 
-    let mut buffer = String::new();
+// Draws one pixel per cycle, left to right and top to bottom; the pixel is lit if the 3-wide
+// sprite centered on X covers it
+struct Crt {
+    width: usize,
+    height: usize,
+    lit: Vec<bool>,
+}
 
-    let mut next_move = moves.pop_front();
+impl Crt {
+    fn new(width: usize, height: usize) -> Crt {
+        Crt {
+            width,
+            height,
+            lit: vec![false; width * height],
+        }
+    }
 
-    for timer in 1..241 {
-        // gets you in the 0-39 range
-        // note the difference between timer (1-indexed) and x-pos (0-indexed), which i hate
-        let timer_x = (timer - 1) % 40;
-        if timer_x == 0 {
+    // every row starts on a new line, so it lines up when printed after "Answer: "
+    fn render(&self) -> String {
+        let mut buffer = String::new();
+        for row in self.lit.chunks(self.width) {
             buffer.push('\n');
+            buffer.extend(row.iter().map(|lit| if *lit { '#' } else { '.' }));
         }
-        if (timer_x - x_val).abs() <= 1 {
-            buffer.push('#');
-        } else {
-            buffer.push('.');
+        buffer
+    }
+}
+
+impl Peripheral for Crt {
+    fn tick(&mut self, cycle: usize, registers: &Registers) {
+        // note the difference between the cycle (1-indexed) and pixel (0-indexed), which i hate
+        let pixel = cycle - 1;
+        if pixel >= self.width * self.height {
+            return;
         }
 
-        if let Some((move_time, diff)) = next_move {
-            if move_time == timer {
-                x_val += diff;
-                next_move = moves.pop_front();
+        let col = (pixel % self.width) as i64;
+        self.lit[pixel] = (col - registers.x).abs() <= 1;
+    }
+}
+
+// Writes a program which makes the CRT draw the picture (rows of # and .), or explains why
+// there isn't one.
+//
+// X only changes at the end of an addx, which holds the old value for two cycles, so the
+// question is which X to have during each cycle, keeping in mind it can only change after two
+// cycles at the same value. We find that by working forward through the instruction boundaries,
+// noting every X we could have at each one, then backtracking from the end.
+fn assemble(picture: &str, width: usize, height: usize) -> Result<Vec<Instruction>, String> {
+    let rows: Vec<&str> = picture
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    if rows.len() != height {
+        return Err(format!(
+            "The screen is {} rows tall, but the picture has {}",
+            height,
+            rows.len()
+        ));
+    }
+    if let Some((row, line)) = rows
+        .iter()
+        .enumerate()
+        .find(|(_, line)| line.len() != width)
+    {
+        return Err(format!(
+            "Row {} of the picture is {} wide, but the screen is {} wide",
+            row + 1,
+            line.len(),
+            width
+        ));
+    }
+    let pixels: Vec<bool> = rows
+        .iter()
+        .flat_map(|line| line.chars().map(|c| c == '#'))
+        .collect();
+    let num_cycles = pixels.len();
+
+    // X way off either side of the screen draws nothing, so these are all the values worth trying
+    let x_values: Vec<i64> = (-2..=width as i64 + 1).collect();
+    let allowed = |cycle_idx: usize, x: i64| {
+        let col = (cycle_idx % width) as i64;
+        pixels[cycle_idx] == ((col - x).abs() <= 1)
+    };
+
+    // came_from[t][x] says how we got to the boundary before cycle index t with X = x: the
+    // instruction just finished, and the X before it
+    let mut came_from: Vec<Vec<Option<(Instruction, i64)>>> =
+        vec![vec![None; x_values.len()]; num_cycles + 1];
+    let x_idx = |x: i64| (x + 2) as usize;
+
+    let mut reachable = vec![vec![false; x_values.len()]; num_cycles + 1];
+    reachable[0][x_idx(1)] = true;
+
+    for t in 0..num_cycles {
+        for &x in x_values.iter() {
+            if !reachable[t][x_idx(x)] || !allowed(t, x) {
+                continue;
+            }
+
+            if !reachable[t + 1][x_idx(x)] {
+                reachable[t + 1][x_idx(x)] = true;
+                came_from[t + 1][x_idx(x)] = Some((Instruction::Noop, x));
             }
+
+            if t + 2 <= num_cycles && allowed(t + 1, x) {
+                for &next_x in x_values.iter() {
+                    if !reachable[t + 2][x_idx(next_x)] {
+                        reachable[t + 2][x_idx(next_x)] = true;
+                        came_from[t + 2][x_idx(next_x)] = Some((Instruction::Addx(next_x - x), x));
+                    }
+                }
+            }
+        }
+    }
+
+    let end_x = match x_values.iter().find(|&&x| reachable[num_cycles][x_idx(x)]) {
+        Some(x) => *x,
+        None => {
+            let stuck_at = (0..=num_cycles)
+                .rev()
+                .find(|&t| reachable[t].iter().any(|r| *r))
+                .unwrap();
+            return Err(format!(
+                "No program draws this picture; the furthest any gets is pixel {} (row {}, column {})",
+                stuck_at + 1,
+                stuck_at / width + 1,
+                stuck_at % width + 1
+            ));
+        }
+    };
+
+    let mut program = Vec::new();
+    let (mut t, mut x) = (num_cycles, end_x);
+    while t > 0 {
+        let (instruction, prev_x) = came_from[t][x_idx(x)].unwrap();
+        program.push(instruction);
+        t -= instruction.cycles();
+        x = prev_x;
+    }
+    program.reverse();
+
+    Ok(program)
+}
+
+// The program, one line per cycle, with X during that cycle alongside; an addx shows up on both
+// of its cycles, and says what X becomes once it's done
+fn annotate(program: &[Instruction]) -> String {
+    let mut cpu = Cpu::new(program.to_vec());
+    let mut lines = Vec::new();
+
+    loop {
+        let pc = cpu.pc;
+        let first_cycle = cpu.cycles_into_instruction == 0;
+        let (cycle, registers) = match cpu.step() {
+            Some(step) => step,
+            None => break,
+        };
+        let instruction = program[pc];
+
+        let mut text = if first_cycle {
+            instruction.to_string()
+        } else {
+            format!("  ({})", instruction)
+        };
+        if cpu.pc != pc && cpu.registers != registers {
+            text.push_str(&format!(" -> X={}", cpu.registers.x));
         }
+
+        lines.push(format!("{:>4}  X={:<4} {}", cycle, registers.x, text));
+    }
+
+    lines.join("\n")
+}
+
+mod parse {
+    use nom::{branch::alt, bytes::complete::tag, combinator::map, sequence::preceded, IResult};
+
+    use super::Instruction;
+    use crate::parsing::{parse_lines, signed};
+
+    fn parse_line_helper(input: &str) -> IResult<&str, Instruction> {
+        alt((
+            map(tag("noop"), |_| Instruction::Noop),
+            map(preceded(tag("addx "), signed), Instruction::Addx),
+        ))(input)
     }
 
-    buffer
+    pub(super) fn parse_input(input: &str) -> Vec<Instruction> {
+        parse_lines(parse_line_helper, input.trim()).unwrap()
+    }
 }
 
 #[cfg(test)]
@@ -264,7 +544,10 @@ noop";
 
     #[test]
     fn sample_a() {
-        assert_eq!(a_with_input(SAMPLE_INPUT_STR), 13140);
+        assert_eq!(
+            a_with_input(SAMPLE_INPUT_STR, &[20, 60, 100, 140, 180, 220]),
+            13140
+        );
     }
 
     #[test]
@@ -281,4 +564,88 @@ noop";
         println!("Expected:\n{}", expected.trim());
         assert_eq!(actual.trim(), expected.trim());
     }
+
+    #[test]
+    fn cycle_timing() {
+        let program = parse::parse_input("noop\naddx 3\naddx -5");
+        let cycles: Vec<(usize, i64)> = Cpu::new(program).collect();
+
+        // X only changes after both cycles of an addx
+        assert_eq!(cycles, vec![(1, 1), (2, 1), (3, 1), (4, 4), (5, 4)]);
+
+        let mut cpu = Cpu::new(parse::parse_input(SAMPLE_INPUT_STR));
+        let x_during = |cycle: usize, cpu: &mut Cpu| cpu.find(|(c, _)| *c == cycle).unwrap().1;
+        assert_eq!(x_during(20, &mut cpu), 21);
+        assert_eq!(x_during(60, &mut cpu), 19);
+        assert_eq!(x_during(220, &mut cpu), 18);
+        assert_eq!(cpu.count(), 20);
+    }
+
+    #[test]
+    fn assemble_pictures() {
+        let check = |picture: &str| {
+            let program = assemble(picture, 40, 6).unwrap();
+            let mut crt = Crt::new(40, 6);
+            Cpu::new(program).run(&mut [&mut crt]);
+            assert_eq!(crt.render().trim(), picture.trim());
+        };
+
+        check(&b_with_input(SAMPLE_INPUT_STR));
+        check(
+            "
+###..#....###...##..####.###...##..#....
+#..#.#....#..#.#..#.#....#..#.#..#.#....
+#..#.#....#..#.#..#.###..###..#....#....
+###..#....###..####.#....#..#.#....#....
+#....#....#....#..#.#....#..#.#..#.#....
+#....####.#....#..#.#....###...##..####.",
+        );
+
+        // X starts at 1, and can't change before the end of the second cycle, so the first two
+        // pixels are always lit
+        let blank_start = format!(
+            ".{}{}",
+            "#".repeat(39),
+            format!("\n{}", ".".repeat(40)).repeat(5)
+        );
+        let err = assemble(&blank_start, 40, 6).unwrap_err();
+        assert!(err.contains("pixel 1 "), "{}", err);
+
+        // pictures the screen can't show at all
+        let short = format!("{}\n", ".".repeat(40)).repeat(5);
+        assert_eq!(
+            assemble(&short, 40, 6).unwrap_err(),
+            "The screen is 6 rows tall, but the picture has 5"
+        );
+        let ragged = format!(
+            "{}\n{}",
+            ".".repeat(39),
+            format!("{}\n", ".".repeat(40)).repeat(5)
+        );
+        assert_eq!(
+            assemble(&ragged, 40, 6).unwrap_err(),
+            "Row 1 of the picture is 39 wide, but the screen is 40 wide"
+        );
+    }
+
+    #[test]
+    fn annotated() {
+        let program = parse::parse_input("noop\naddx 3\naddx -5");
+        assert_eq!(
+            annotate(&program),
+            "   1  X=1    noop
+   2  X=1    addx 3
+   3  X=1      (addx 3) -> X=4
+   4  X=4    addx -5
+   5  X=4      (addx -5) -> X=-1"
+        );
+
+        // and it round-trips through the input syntax
+        let text = program
+            .iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(parse::parse_input(&text), program);
+    }
 }
@@ -1,13 +1,31 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::rc::Rc;
+use std::str::FromStr;
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_11.txt").expect("Should be able to read the file")
+use gcd::Gcd;
+use ibig::IBig;
+
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_11.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
+
+    let rounds = params.get("rounds", 20);
+    let mode = params.get("worry", WorryMode::Auto);
 
-    let val = a_with_input(&contents);
+    let trace: String = params.get("trace", String::new());
+    if !trace.is_empty() {
+        return trace_with_input(&contents, &trace, rounds, 3, mode);
+    }
+
+    let val = a_with_input(&contents, rounds, mode, params.get("brute", false));
 
     val.to_string()
 }
@@ -18,19 +36,19 @@ mod parse {
     use nom::{
         branch::alt,
         bytes::complete::tag,
-        character::complete::{digit1, multispace0, multispace1},
-        combinator::{eof, map},
-        multi::{many0, separated_list1},
-        sequence::tuple,
+        character::complete::{char, multispace0, multispace1, space0},
+        combinator::map,
+        multi::{fold_many0, separated_list1},
+        sequence::{delimited, pair, preceded, tuple},
         IResult,
     };
 
-    use super::{Arg, Monkey, Op, WorryAction};
+    use super::{Expr, Monkey};
+    use crate::parsing::{blocks, parse_all, unsigned};
 
     fn parse_monkey_line(input: &str) -> IResult<&str, usize> {
-        let (out, val) = tuple((tag("Monkey "), digit1, tag(":"), multispace0))(input)?;
-        let (_, digits, _, _) = val;
-        let num: usize = digits.parse::<usize>().unwrap();
+        let (out, val) = tuple((tag("Monkey "), unsigned, tag(":"), multispace0))(input)?;
+        let (_, num, _, _) = val;
         Ok((out, num))
     }
 
@@ -38,51 +56,66 @@ mod parse {
         let (out, val) = tuple((
             tag("Starting items:"),
             multispace0,
-            separated_list1(tag(", "), digit1),
+            separated_list1(tag(", "), unsigned),
             multispace0,
         ))(input)?;
 
         let (_, _, items, _) = val;
-        let items = items
-            .iter()
-            .map(|tok| tok.parse::<u64>().unwrap())
-            .collect();
-        Ok((out, items))
+        Ok((out, items.into()))
+    }
+
+    // usual precedence: expr is terms joined by + and -, terms are atoms joined by *, and an atom
+    // is old, a number, or a parenthesized expr
+    fn expr(input: &str) -> IResult<&str, Expr> {
+        let (input, first) = term(input)?;
+
+        fold_many0(
+            pair(delimited(space0, alt((char('+'), char('-'))), space0), term),
+            move || first.clone(),
+            |acc, (op, next)| match op {
+                '+' => Expr::Add(Box::new(acc), Box::new(next)),
+                _ => Expr::Sub(Box::new(acc), Box::new(next)),
+            },
+        )(input)
     }
 
-    fn parse_op(input: &str) -> IResult<&str, Op> {
-        let parse_add = map(tag("+"), |_| Op::Add);
-        let parse_mul = map(tag("*"), |_| Op::Mul);
+    fn term(input: &str) -> IResult<&str, Expr> {
+        let (input, first) = atom(input)?;
 
-        alt((parse_add, parse_mul))(input)
+        fold_many0(
+            preceded(delimited(space0, char('*'), space0), atom),
+            move || first.clone(),
+            |acc, next| Expr::Mul(Box::new(acc), Box::new(next)),
+        )(input)
     }
 
-    fn parse_rhs(input: &str) -> IResult<&str, Arg> {
-        let parse_old = map(tag("old"), |_| Arg::Old);
-        let parse_num = map(digit1, |digits: &str| {
-            Arg::Num(digits.parse::<u64>().unwrap())
-        });
+    fn atom(input: &str) -> IResult<&str, Expr> {
+        let parse_old = map(tag("old"), |_| Expr::Old);
+        let parse_num = map(unsigned, Expr::Num);
+        let parse_parens = delimited(pair(char('('), space0), expr, pair(space0, char(')')));
 
-        alt((parse_num, parse_old))(input)
+        alt((parse_old, parse_num, parse_parens))(input)
     }
 
-    fn operation_line(input: &str) -> IResult<&str, WorryAction> {
+    fn operation_line(input: &str) -> IResult<&str, Expr> {
         let (input, _) = multispace0(input)?;
-        let (input, _) = tag("Operation: new = old")(input)?;
-        let (input, _) = multispace1(input)?;
-        let (input, op) = parse_op(input)?;
-        let (input, _) = multispace1(input)?;
-        let (input, rhs) = parse_rhs(input)?;
+        let (input, _) = tag("Operation: new =")(input)?;
+        let (input, _) = space0(input)?;
+        let (input, operation) = expr(input)?;
         let (input, _) = multispace0(input)?;
 
-        Ok((input, WorryAction { op, rhs }))
+        Ok((input, operation))
     }
 
     fn test_line(input: &str) -> IResult<&str, u64> {
-        let (out, val) =
-            tuple((tag("Test: divisible by"), multispace1, digit1, multispace0))(input)?;
-        let (_, _, digits, _) = val;
-        Ok((out, digits.parse::<u64>().unwrap()))
+        let (out, val) = tuple((
+            tag("Test: divisible by"),
+            multispace1,
+            unsigned,
+            multispace0,
+        ))(input)?;
+        let (_, _, num, _) = val;
+        Ok((out, num))
     }
 
     fn if_true_line(input: &str) -> IResult<&str, usize> {
@@ -90,11 +123,11 @@ mod parse {
             multispace0,
             tag("If true: throw to monkey"),
             multispace1,
-            digit1,
+            unsigned,
             multispace0,
         ))(input)?;
-        let (_, _, _, digits, _) = val;
-        Ok((out, digits.parse::<usize>().unwrap()))
+        let (_, _, _, num, _) = val;
+        Ok((out, num))
     }
 
     fn if_false_line(input: &str) -> IResult<&str, usize> {
@@ -103,11 +136,11 @@ mod parse {
             multispace0,
             tag("If false: throw to monkey"),
             multispace1,
-            digit1,
+            unsigned,
             multispace0,
         ))(input)?;
-        let (_, _, _, digits, _) = val;
-        Ok((out, digits.parse::<usize>().unwrap()))
+        let (_, _, _, num, _) = val;
+        Ok((out, num))
     }
 
     fn parse_monkey(input: &str) -> IResult<&str, Monkey> {
@@ -134,17 +167,10 @@ mod parse {
         ))
     }
 
-    fn parse_helper(input: &str) -> IResult<&str, Vec<Monkey>> {
-        let (input, monkeys) = many0(parse_monkey)(input)?;
-        let (_, _) = eof(input)?;
-        Ok(("", monkeys))
-    }
-
     pub(super) fn parse_input(input: &str) -> Vec<Monkey> {
-        // just error "handling" around the nom stuff
-        let (remaining, monkeys) = parse_helper(input).unwrap();
-        assert_eq!(remaining, "");
-        monkeys
+        blocks(input)
+            .map(|block| parse_all(parse_monkey, block).unwrap())
+            .collect()
     }
 
     #[cfg(test)]
@@ -174,10 +200,7 @@ mod parse {
             let expected = Monkey {
                 idx: 0,
                 items: vec![79, 98].into(),
-                operation: WorryAction {
-                    op: Op::Mul,
-                    rhs: Arg::Num(19),
-                },
+                operation: Expr::Mul(Box::new(Expr::Old), Box::new(Expr::Num(19))),
                 div_test: 23,
                 if_true_goal: 2,
                 if_false_goal: 3,
@@ -185,161 +208,600 @@ mod parse {
 
             assert_eq!(actual, expected);
         }
+
+        #[test]
+        fn expressions() {
+            let (rest, actual) =
+                operation_line("Operation: new = old * (old - 3) + 2 * old").unwrap();
+            assert_eq!(rest, "");
+
+            let old = || Box::new(Expr::Old);
+            let num = |n| Box::new(Expr::Num(n));
+            let expected = Expr::Add(
+                Box::new(Expr::Mul(old(), Box::new(Expr::Sub(old(), num(3))))),
+                Box::new(Expr::Mul(num(2), old())),
+            );
+            assert_eq!(actual, expected);
+
+            // left associative
+            let (_, actual) = operation_line("Operation: new = old - 1 - 2").unwrap();
+            assert_eq!(
+                actual,
+                Expr::Sub(Box::new(Expr::Sub(old(), num(1))), num(2))
+            );
+        }
     }
 }
 
-fn a_with_input(input: &str) -> usize {
-    let mut monkeys = parse::parse_input(input);
-    a_parsed(&mut monkeys)
+fn a_with_input(input: &str, num_rounds: usize, mode: WorryMode, brute: bool) -> u128 {
+    let monkeys = parse::parse_input(input);
+    run(&monkeys, num_rounds, 3, mode, brute).unwrap_or_else(|e| panic!("{}", e))
 }
 
-fn a_parsed(monkeys: &mut [Monkey]) -> usize {
-    assert!(monkeys.len() >= 2);
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
 
-    let mut inspection_counts: Vec<usize> = vec![0; monkeys.len()];
-    const NUM_ROUNDS: usize = 20;
+    let rounds = params.get("rounds", 10000);
+    let mode = params.get("worry", WorryMode::Auto);
 
-    for _ in 0..NUM_ROUNDS {
-        simulate_round_a(monkeys, &mut inspection_counts);
+    let trace: String = params.get("trace", String::new());
+    if !trace.is_empty() {
+        return trace_with_input(&contents, &trace, rounds, 1, mode);
     }
 
-    // now we can compute the MONKEY BUSINESS
+    let val = b_with_input(&contents, rounds, mode, params.get("brute", false));
+
+    val.to_string()
+}
+
+fn b_with_input(input: &str, num_rounds: usize, mode: WorryMode, brute: bool) -> u128 {
+    let monkeys = parse::parse_input(input);
+    run(&monkeys, num_rounds, 1, mode, brute).unwrap_or_else(|e| panic!("{}", e))
+}
+
+fn trace_with_input(
+    input: &str,
+    item: &str,
+    num_rounds: usize,
+    relief: u64,
+    mode: WorryMode,
+) -> String {
+    let monkeys = parse::parse_input(input);
+    let item_idx = item
+        .parse()
+        .unwrap_or_else(|_| panic!("Expected an item number to trace, got {:?}", item));
+    trace_item(&monkeys, item_idx, num_rounds, relief, mode).unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Counts the inspections with whichever worry representation is appropriate, and computes the
+// MONKEY BUSINESS. Worry is divided by `relief` after each inspection (1 for no relief). With
+// `brute`, every round is simulated in full; otherwise each item is followed on its own.
+fn run(
+    monkeys: &[Monkey],
+    num_rounds: usize,
+    relief: u64,
+    mode: WorryMode,
+    brute: bool,
+) -> Result<u128, String> {
+    assert!(monkeys.len() >= 2);
+    assert!(relief > 0, "Relief has to divide by something");
+
+    let counts = match choose_mode(monkeys, relief, mode)? {
+        WorryMode::Exact => {
+            let relief = IBig::from(relief);
+            count_inspections(monkeys, num_rounds, IBig::from, |w| w / &relief, brute)
+        }
+        WorryMode::Residue => {
+            let modulus = combined_modulus(monkeys).unwrap();
+            let make = |n| Residue::new(n, modulus);
+            count_inspections(monkeys, num_rounds, make, |w| w, brute)
+        }
+        WorryMode::PerMonkey => {
+            let moduli: Rc<[u64]> = monkeys.iter().map(|m| m.div_test).collect();
+            let make = |n| PerMonkey::new(n, &moduli);
+            count_inspections(monkeys, num_rounds, make, |w| w, brute)
+        }
+        WorryMode::Auto => unreachable!("choose_mode always picks something"),
+    };
+
+    Ok(monkey_business(counts))
+}
+
+// For --param trace=N: where the Nth item (counting through the starting items in order) goes
+// each round, and when it starts repeating itself
+fn trace_item(
+    monkeys: &[Monkey],
+    item_idx: usize,
+    num_rounds: usize,
+    relief: u64,
+    mode: WorryMode,
+) -> Result<String, String> {
+    let (monkey_idx, start) = starting_items(monkeys)
+        .nth(item_idx)
+        .ok_or_else(|| format!("There is no item {}", item_idx))?;
+
+    let rendered = match choose_mode(monkeys, relief, mode)? {
+        WorryMode::Exact => {
+            let relief = IBig::from(relief);
+            let relieve = |w| w / &relief;
+            track_item(monkeys, monkey_idx, IBig::from(start), num_rounds, &relieve).render()
+        }
+        WorryMode::Residue => {
+            let start = Residue::new(start, combined_modulus(monkeys).unwrap());
+            track_item(monkeys, monkey_idx, start, num_rounds, &|w| w).render()
+        }
+        WorryMode::PerMonkey => {
+            let moduli: Rc<[u64]> = monkeys.iter().map(|m| m.div_test).collect();
+            let start = PerMonkey::new(start, &moduli);
+            track_item(monkeys, monkey_idx, start, num_rounds, &|w| w).render()
+        }
+        WorryMode::Auto => unreachable!("choose_mode always picks something"),
+    };
+
+    Ok(rendered)
+}
+
+// products of big inspection counts (from extrapolating lots of rounds) can overflow a u64
+fn monkey_business(mut inspection_counts: Vec<u64>) -> u128 {
     inspection_counts.sort();
 
-    inspection_counts.pop().unwrap() * inspection_counts.pop().unwrap()
+    inspection_counts.pop().unwrap() as u128 * inspection_counts.pop().unwrap() as u128
 }
 
-fn simulate_round_a(monkeys: &mut [Monkey], inspection_counts: &mut [usize]) {
-    for i in 0..monkeys.len() {
-        inspection_counts[i] += monkeys[i].items.len();
+// Figures out which worry representation to use, or refuses if the requested one would give the
+// wrong answer.
+//
+// Reducing worry mod something only works if every divisibility test gives the same answer
+// before and after the reduction, which is true as long as the modulus is a multiple of every
+// test, and we only ever add, subtract and multiply. Dividing (the relief after each inspection)
+// breaks that, so then only exact arithmetic will do.
+fn choose_mode(monkeys: &[Monkey], relief: u64, requested: WorryMode) -> Result<WorryMode, String> {
+    if relief != 1 {
+        return match requested {
+            WorryMode::Auto | WorryMode::Exact => Ok(WorryMode::Exact),
+            other => Err(format!(
+                "Can't use {:?} worry: dividing by {} after each inspection doesn't commute with \
+                 modular reduction, so only exact worry is sound",
+                other, relief
+            )),
+        };
+    }
 
-        while let Some(mut next) = monkeys[i].items.pop_front() {
-            next = apply_op_a(next, &monkeys[i].operation);
-            let goal_monkey = if apply_div_test(&next, &monkeys[i].div_test) {
-                monkeys[i].if_true_goal
-            } else {
-                monkeys[i].if_false_goal
-            };
-            monkeys[goal_monkey].items.push_back(next);
+    if let Some(zero_idx) = monkeys.iter().position(|m| m.div_test == 0) {
+        return Err(format!("Monkey {} tests divisibility by 0", zero_idx));
+    }
+
+    match requested {
+        WorryMode::Auto => match combined_modulus(monkeys) {
+            Some(_) => Ok(WorryMode::Residue),
+            // one big modulus would overflow, but each monkey's own is fine
+            None => Ok(WorryMode::PerMonkey),
+        },
+        WorryMode::Residue if combined_modulus(monkeys).is_none() => Err(
+            "Can't use residue worry: the divisibility tests have no common multiple that fits in \
+             a u64; try crt instead"
+                .to_string(),
+        ),
+        other => Ok(other),
+    }
+}
+
+// least common multiple of every divisibility test, if it fits
+fn combined_modulus(monkeys: &[Monkey]) -> Option<u64> {
+    monkeys.iter().try_fold(1_u64, |acc, m| {
+        (acc / acc.gcd(m.div_test)).checked_mul(m.div_test)
+    })
+}
+
+fn count_inspections<W: Worry + Eq + Hash>(
+    monkeys: &[Monkey],
+    num_rounds: usize,
+    make: impl Fn(u64) -> W,
+    relieve: impl Fn(W) -> W,
+    brute: bool,
+) -> Vec<u64> {
+    if brute {
+        return simulate(monkeys, num_rounds, make, relieve);
+    }
+
+    // items never affect each other, so the counts just add up
+    let mut inspection_counts = vec![0; monkeys.len()];
+    for (monkey_idx, start) in starting_items(monkeys) {
+        let trace = track_item(monkeys, monkey_idx, make(start), num_rounds, &relieve);
+        let item_counts = trace.inspection_counts(monkeys.len(), num_rounds);
+        for (total, count) in inspection_counts.iter_mut().zip(item_counts) {
+            *total += count;
         }
     }
+
+    inspection_counts
 }
 
-fn apply_div_test(num: &u64, test: &u64) -> bool {
-    num % test == 0
+// every starting item, as (which monkey has it, its worry level), in input order
+fn starting_items(monkeys: &[Monkey]) -> impl Iterator<Item = (usize, u64)> + '_ {
+    monkeys
+        .iter()
+        .enumerate()
+        .flat_map(|(i, m)| m.items.iter().map(move |&worry| (i, worry)))
 }
 
-fn apply_op_a(old_val: u64, op: &WorryAction) -> u64 {
-    let base = match op.op {
-        Op::Add => match &op.rhs {
-            Arg::Num(num) => &old_val + num,
-            Arg::Old => &old_val + &old_val,
-        },
-        Op::Mul => match &op.rhs {
-            Arg::Num(num) => &old_val * num,
-            Arg::Old => &old_val * &old_val,
-        },
+// Runs the rounds and counts how many items each monkey inspects. `make` turns a starting worry
+// level into the chosen representation, and `relieve` is applied after each inspection.
+fn simulate<W: Worry>(
+    monkeys: &[Monkey],
+    num_rounds: usize,
+    make: impl Fn(u64) -> W,
+    relieve: impl Fn(W) -> W,
+) -> Vec<u64> {
+    let mut items: Vec<VecDeque<W>> = monkeys
+        .iter()
+        .map(|m| m.items.iter().map(|&n| make(n)).collect())
+        .collect();
+    let mut inspection_counts: Vec<u64> = vec![0; monkeys.len()];
+
+    for _ in 0..num_rounds {
+        for (i, monkey) in monkeys.iter().enumerate() {
+            inspection_counts[i] += items[i].len() as u64;
+
+            while let Some(next) = items[i].pop_front() {
+                let next = relieve(monkey.operation.eval(&next));
+                items[monkey.throw_to(&next)].push_back(next);
+            }
+        }
+    }
+
+    inspection_counts
+}
+
+// Follows one item through the rounds, until it's done them all, or until it's somewhere it's
+// been before at the start of a round (same monkey, same worry), after which it'll just do the
+// same thing over and over.
+//
+// Within a round, an item thrown to a later monkey gets inspected again that round; one thrown to
+// an earlier monkey waits for the next round.
+fn track_item<W: Worry + Eq + Hash>(
+    monkeys: &[Monkey],
+    monkey_idx: usize,
+    start: W,
+    num_rounds: usize,
+    relieve: &impl Fn(W) -> W,
+) -> ItemTrace<W> {
+    let mut seen: HashMap<(usize, W), usize> = HashMap::new();
+    let mut trace = ItemTrace {
+        starts: Vec::new(),
+        inspections: Vec::new(),
+        cycle: None,
     };
 
-    &base / 3_u64
+    let mut state = (monkey_idx, start);
+    for round in 0..num_rounds {
+        if let Some(&first_seen) = seen.get(&state) {
+            trace.cycle = Some((first_seen, round - first_seen));
+            break;
+        }
+        seen.insert(state.clone(), round);
+        trace.starts.push(state.clone());
+
+        let (mut holder, mut worry) = state;
+        let mut inspected_by = Vec::new();
+        loop {
+            inspected_by.push(holder);
+            worry = relieve(monkeys[holder].operation.eval(&worry));
+            let next_holder = monkeys[holder].throw_to(&worry);
+            let round_over = next_holder <= holder;
+            holder = next_holder;
+            if round_over {
+                break;
+            }
+        }
+
+        trace.inspections.push(inspected_by);
+        state = (holder, worry);
+    }
+
+    trace
+}
+
+// One item's history, as found by track_item
+#[derive(Debug)]
+struct ItemTrace<W> {
+    // who had the item at the start of each round, and how worrying it was
+    starts: Vec<(usize, W)>,
+    // which monkeys inspected it during each round, in order
+    inspections: Vec<Vec<usize>>,
+    // (first round of the cycle, cycle length), if the item started repeating itself; every
+    // round after the ones recorded is the same as the one cycle_length before it
+    cycle: Option<(usize, usize)>,
 }
 
-pub fn b() -> String {
-    let contents = input();
+impl<W: Display> ItemTrace<W> {
+    // how many times each monkey inspects the item over the first num_rounds rounds
+    fn inspection_counts(&self, num_monkeys: usize, num_rounds: usize) -> Vec<u64> {
+        let mut counts = vec![0; num_monkeys];
+        let mut add = |rounds: std::ops::Range<usize>, times: u64| {
+            for round in rounds {
+                for &monkey in self.inspections[round].iter() {
+                    counts[monkey] += times;
+                }
+            }
+        };
+
+        match self.cycle {
+            Some((cycle_start, cycle_len)) if num_rounds > self.inspections.len() => {
+                let remaining = num_rounds - cycle_start;
+                add(0..cycle_start, 1);
+                add(
+                    cycle_start..cycle_start + cycle_len,
+                    (remaining / cycle_len) as u64,
+                );
+                add(cycle_start..cycle_start + remaining % cycle_len, 1);
+            }
+            _ => add(0..num_rounds, 1),
+        }
 
-    let val = b_with_input(&contents);
+        counts
+    }
 
-    val.to_string()
+    fn render(&self) -> String {
+        let mut lines: Vec<String> = self
+            .starts
+            .iter()
+            .zip(self.inspections.iter())
+            .enumerate()
+            .map(|(round, ((_, worry), inspected_by))| {
+                let path: Vec<String> = inspected_by.iter().map(|m| m.to_string()).collect();
+                format!(
+                    "round {}: worry {}, inspected by monkey {}",
+                    round + 1,
+                    worry,
+                    path.join(" then ")
+                )
+            })
+            .collect();
+
+        if let Some((cycle_start, cycle_len)) = self.cycle {
+            lines.push(format!(
+                "then rounds {} to {} repeat forever",
+                cycle_start + 1,
+                cycle_start + cycle_len
+            ));
+        }
+
+        format!("\n{}", lines.join("\n"))
+    }
 }
 
-fn b_with_input(input: &str) -> usize {
-    let mut monkeys = parse::parse_input(input);
-    b_parsed(&mut monkeys)
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum WorryMode {
+    // pick whichever of the others is sound (and cheapest)
+    Auto,
+    // the actual worry level, as a bigint; always right, but can get very big
+    Exact,
+    // worry mod the least common multiple of all the divisibility tests
+    Residue,
+    // worry mod each monkey's divisibility test separately (chinese remainder theorem style),
+    // for when their common multiple is too big to work with
+    PerMonkey,
 }
 
-fn b_parsed(monkeys: &mut [Monkey]) -> usize {
-    assert!(monkeys.len() >= 2);
+impl FromStr for WorryMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(WorryMode::Auto),
+            "exact" => Ok(WorryMode::Exact),
+            "residue" => Ok(WorryMode::Residue),
+            "crt" => Ok(WorryMode::PerMonkey),
+            other => Err(format!(
+                "Unknown worry mode {:?}; expected auto, exact, residue or crt",
+                other
+            )),
+        }
+    }
+}
+
+// Something which can stand in for an item's worry level: it supports the arithmetic in the
+// operations, and can answer the divisibility tests
+trait Worry: Clone + Display {
+    // the number in the same representation as self (e.g. with the same modulus)
+    fn constant(&self, num: u64) -> Self;
+
+    fn add(&self, other: &Self) -> Self;
+
+    fn sub(&self, other: &Self) -> Self;
 
-    let mut inspection_counts: Vec<usize> = vec![0; monkeys.len()];
-    const NUM_ROUNDS: usize = 10000;
+    fn mul(&self, other: &Self) -> Self;
 
-    let combined_modulus: u64 = monkeys.iter().map(|m| m.div_test).product();
+    fn divisible_by(&self, divisor: u64) -> bool;
+}
 
-    for _ in 0..NUM_ROUNDS {
-        simulate_round_b(monkeys, &mut inspection_counts, combined_modulus);
+impl Worry for IBig {
+    fn constant(&self, num: u64) -> Self {
+        IBig::from(num)
     }
 
-    // now we can compute the MONKEY BUSINESS
-    inspection_counts.sort();
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
 
-    inspection_counts.pop().unwrap() * inspection_counts.pop().unwrap()
+    fn sub(&self, other: &Self) -> Self {
+        self - other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn divisible_by(&self, divisor: u64) -> bool {
+        self % IBig::from(divisor) == IBig::from(0_u8)
+    }
 }
 
-fn simulate_round_b(
-    monkeys: &mut [Monkey],
-    inspection_counts: &mut [usize],
-    combined_modulus: u64,
-) {
-    for i in 0..monkeys.len() {
-        inspection_counts[i] += monkeys[i].items.len();
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+struct Residue {
+    // CONTRACT: value < modulus
+    value: u64,
+    modulus: u64,
+}
 
-        while let Some(mut next) = monkeys[i].items.pop_front() {
-            next = apply_op_b(next, monkeys[i].operation, combined_modulus);
-            let goal_monkey = if next % monkeys[i].div_test == 0 {
-                monkeys[i].if_true_goal
-            } else {
-                monkeys[i].if_false_goal
-            };
-            monkeys[goal_monkey].items.push_back(next);
+impl Residue {
+    fn new(num: u64, modulus: u64) -> Residue {
+        Residue {
+            value: num % modulus,
+            modulus,
+        }
+    }
+
+    // u128 so the intermediate result can't overflow, whatever the modulus
+    fn reduce(&self, num: u128) -> Residue {
+        Residue {
+            value: (num % self.modulus as u128) as u64,
+            modulus: self.modulus,
         }
     }
 }
 
-fn apply_op_b(old_val: u64, op: WorryAction, modulus: u64) -> u64 {
-    let base = match op.op {
-        Op::Add => match op.rhs {
-            Arg::Num(num) => old_val + num,
-            Arg::Old => old_val + old_val,
-        },
-        Op::Mul => match op.rhs {
-            Arg::Num(num) => old_val * num,
-            Arg::Old => old_val * old_val,
-        },
-    };
+impl Display for Residue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (mod {})", self.value, self.modulus)
+    }
+}
+
+impl Worry for Residue {
+    fn constant(&self, num: u64) -> Self {
+        Residue::new(num, self.modulus)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self.reduce(self.value as u128 + other.value as u128)
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self.reduce(self.value as u128 + self.modulus as u128 - other.value as u128)
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self.reduce(self.value as u128 * other.value as u128)
+    }
+
+    fn divisible_by(&self, divisor: u64) -> bool {
+        debug_assert_eq!(self.modulus % divisor, 0);
+        self.value.is_multiple_of(divisor)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+struct PerMonkey {
+    // residues[i] is the worry mod moduli[i]
+    residues: Vec<u64>,
+    moduli: Rc<[u64]>,
+}
+
+impl PerMonkey {
+    fn new(num: u64, moduli: &Rc<[u64]>) -> PerMonkey {
+        PerMonkey {
+            residues: moduli.iter().map(|m| num % m).collect(),
+            moduli: moduli.clone(),
+        }
+    }
 
-    base % modulus
+    fn combine(&self, other: &PerMonkey, f: impl Fn(u128, u128, u128) -> u128) -> PerMonkey {
+        let residues = self
+            .residues
+            .iter()
+            .zip(other.residues.iter())
+            .zip(self.moduli.iter())
+            .map(|((&a, &b), &m)| (f(a as u128, b as u128, m as u128) % m as u128) as u64)
+            .collect();
+
+        PerMonkey {
+            residues,
+            moduli: self.moduli.clone(),
+        }
+    }
+}
+
+impl Display for PerMonkey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let residues: Vec<String> = self
+            .residues
+            .iter()
+            .zip(self.moduli.iter())
+            .map(|(r, m)| format!("{} (mod {})", r, m))
+            .collect();
+        write!(f, "{}", residues.join(", "))
+    }
+}
+
+impl Worry for PerMonkey {
+    fn constant(&self, num: u64) -> Self {
+        PerMonkey::new(num, &self.moduli)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self.combine(other, |a, b, _| a + b)
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self.combine(other, |a, b, m| a + m - b)
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self.combine(other, |a, b, _| a * b)
+    }
+
+    fn divisible_by(&self, divisor: u64) -> bool {
+        let idx = self
+            .moduli
+            .iter()
+            .position(|m| m % divisor == 0)
+            .unwrap_or_else(|| panic!("Not keeping track of worry mod {}", divisor));
+        self.residues[idx].is_multiple_of(divisor)
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
 struct Monkey {
     idx: usize,
-    // represents the item's current worry level (not an index into a canonical map or anything)
-    // in part A the //=3 keeps them manageable; in part B we use modular arithmetic; so we don't
-    // need to worry about bigints
+    // starting worry levels, in the order they'll be inspected
     items: VecDeque<u64>,
-    operation: WorryAction,
+    operation: Expr,
     div_test: u64,
     if_true_goal: usize,
     if_false_goal: usize,
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
-struct WorryAction {
-    op: Op,
-    // lhs is always Arg::Old
-    rhs: Arg,
-}
-
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
-enum Op {
-    Add,
-    Mul,
+impl Monkey {
+    fn throw_to<W: Worry>(&self, worry: &W) -> usize {
+        if worry.divisible_by(self.div_test) {
+            self.if_true_goal
+        } else {
+            self.if_false_goal
+        }
+    }
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
-enum Arg {
+// The right hand side of `new = ...`
+#[derive(Debug, Eq, PartialEq, Clone)]
+enum Expr {
     Old,
     Num(u64),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn eval<W: Worry>(&self, old: &W) -> W {
+        match self {
+            Expr::Old => old.clone(),
+            Expr::Num(num) => old.constant(*num),
+            Expr::Add(lhs, rhs) => lhs.eval(old).add(&rhs.eval(old)),
+            Expr::Sub(lhs, rhs) => lhs.eval(old).sub(&rhs.eval(old)),
+            Expr::Mul(lhs, rhs) => lhs.eval(old).mul(&rhs.eval(old)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -377,14 +839,109 @@ Monkey 3:
     #[test]
     fn sample_a() {
         let input = SAMPLE_INPUT_STR;
-        let actual = a_with_input(input);
+        let actual = a_with_input(input, 20, WorryMode::Auto, false);
         assert_eq!(actual, 10605);
     }
 
     #[test]
     fn sample_b() {
         let input = SAMPLE_INPUT_STR;
-        let actual = b_with_input(input);
+        let actual = b_with_input(input, 10000, WorryMode::Auto, false);
         assert_eq!(actual, 2713310158);
     }
+
+    #[test]
+    fn worry_modes_agree() {
+        // subtraction can make worry negative, which the modular modes have to get right too
+        let input = SAMPLE_INPUT_STR.replace("old + 6", "(old - 100) * 2 - old");
+        let monkeys = parse::parse_input(&input);
+
+        let expected = run(&monkeys, 12, 1, WorryMode::Exact, true).unwrap();
+        assert_eq!(
+            run(&monkeys, 12, 1, WorryMode::Residue, true).unwrap(),
+            expected
+        );
+        assert_eq!(
+            run(&monkeys, 12, 1, WorryMode::PerMonkey, true).unwrap(),
+            expected
+        );
+
+        // and the relief in part A works the same as it always did
+        let monkeys = parse::parse_input(SAMPLE_INPUT_STR);
+        assert_eq!(run(&monkeys, 20, 3, WorryMode::Exact, true).unwrap(), 10605);
+    }
+
+    #[test]
+    fn choosing_modes() {
+        let mut monkeys = parse::parse_input(SAMPLE_INPUT_STR);
+
+        assert_eq!(
+            choose_mode(&monkeys, 1, WorryMode::Auto),
+            Ok(WorryMode::Residue)
+        );
+        assert_eq!(
+            choose_mode(&monkeys, 3, WorryMode::Auto),
+            Ok(WorryMode::Exact)
+        );
+        assert!(choose_mode(&monkeys, 3, WorryMode::Residue).is_err());
+        assert!(choose_mode(&monkeys, 3, WorryMode::PerMonkey).is_err());
+
+        // big coprime tests have a common multiple which doesn't fit in a u64
+        let big_primes = [4294967291, 4294967279, 4294967231, 4294967197];
+        for (monkey, prime) in monkeys.iter_mut().zip(big_primes) {
+            monkey.div_test = prime;
+        }
+        assert_eq!(combined_modulus(&monkeys), None);
+        assert_eq!(
+            choose_mode(&monkeys, 1, WorryMode::Auto),
+            Ok(WorryMode::PerMonkey)
+        );
+        assert!(choose_mode(&monkeys, 1, WorryMode::Residue).is_err());
+    }
+
+    #[test]
+    fn tracking_items() {
+        let monkeys = parse::parse_input(SAMPLE_INPUT_STR);
+
+        // following items one at a time gives the same counts as simulating everything at once
+        for (rounds, relief) in [(20, 3), (1000, 1), (1234, 1)] {
+            let brute = run(&monkeys, rounds, relief, WorryMode::Auto, true).unwrap();
+            let tracked = run(&monkeys, rounds, relief, WorryMode::Auto, false).unwrap();
+            assert_eq!(tracked, brute, "{} rounds", rounds);
+        }
+
+        // and keeps working long after brute force would have given up
+        let modulus = combined_modulus(&monkeys).unwrap();
+        let trace = track_item(
+            &monkeys,
+            0,
+            Residue::new(79, modulus),
+            1_000_000_000_000,
+            &|w| w,
+        );
+        let (cycle_start, cycle_len) = trace.cycle.unwrap();
+        assert_eq!(trace.inspections.len(), cycle_start + cycle_len);
+
+        // which is consistent with adding one more cycle's worth of rounds
+        let counts = trace.inspection_counts(4, 1_000_000);
+        let later = trace.inspection_counts(4, 1_000_000 + cycle_len);
+        let one_cycle = trace.inspection_counts(4, cycle_start + cycle_len);
+        let before_cycle = trace.inspection_counts(4, cycle_start);
+        for m in 0..4 {
+            assert_eq!(later[m] - counts[m], one_cycle[m] - before_cycle[m]);
+        }
+    }
+
+    #[test]
+    fn tracing() {
+        // the example walks through the first round in detail: item 0 (worry 79, held by monkey 0)
+        // goes to monkey 3, which then throws it to monkey 1, in round 2
+        let rendered = trace_with_input(SAMPLE_INPUT_STR, "0", 2, 3, WorryMode::Auto);
+        assert_eq!(
+            rendered,
+            "
+round 1: worry 79, inspected by monkey 0 then 3
+round 2: worry 167, inspected by monkey 1 then 2 then 3"
+        );
+    }
 }
@@ -1,67 +1,181 @@
-use std::collections::VecDeque;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_12.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_12.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
+
+    let rule = climb_rule(params);
 
-    let val = a_with_input(&contents);
+    // --param from=x:y and to=x:y measure between other squares than S and E
+    let from: String = params.get("from", "S".to_string());
+    let to: String = params.get("to", "E".to_string());
+
+    // --param points=x:y,x:y,... gives the distance between every pair of those squares
+    let points = params.get_list("points", &[] as &[String]);
+    if !points.is_empty() {
+        let grid = parse_grid(&contents, rule);
+        let points: Vec<(i32, i32)> = points.iter().map(|p| pos_param(&grid, p)).collect();
+        return distance_table(&grid, &points);
+    }
+
+    if params.get("region", false) {
+        let grid = parse_grid(&contents, rule);
+        let to = pos_param(&grid, &to);
+        return format!(
+            "{} squares can reach {}:{}",
+            grid.region_size(to),
+            to.0,
+            to.1
+        );
+    }
+
+    let show = params.get("show", false);
+    let heights = params.get("heights", false);
+
+    let val = a_with_input(&contents, rule, &from, &to, show.then_some(heights));
 
     val.to_string()
 }
 
-fn a_with_input(input: &str) -> usize {
-    let grid = parse_grid(input);
-    let best_scores = dijkstra(&grid);
+// --param max_up=N and max_down=N change how steep a step can be
+fn climb_rule(params: &Params) -> ClimbRule {
+    ClimbRule {
+        max_up: params.get("max_up", ClimbRule::PUZZLE.max_up),
+        max_down: params.get("max_down", ClimbRule::PUZZLE.max_down),
+    }
+}
+
+// a square given as S, E, or x:y
+fn pos_param(grid: &Grid, raw: &str) -> (i32, i32) {
+    let pos = match raw {
+        "S" => grid.start,
+        "E" => grid.end,
+        _ => raw
+            .split_once(':')
+            .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+            .unwrap_or_else(|| panic!("Expected S, E or x:y for a square, got {:?}", raw)),
+    };
+    assert!(grid.contains_pos(pos), "{:?} is off the map", pos);
+    pos
+}
+
+fn distance_table(grid: &Grid, points: &[(i32, i32)]) -> String {
+    let distances = grid.distances(points, points);
+
+    let mut lines = Vec::new();
+    for (from, row) in points.iter().zip(distances) {
+        for (to, dist) in points.iter().zip(row) {
+            let dist = match dist {
+                Some(dist) => dist.to_string(),
+                None => "unreachable".to_string(),
+            };
+            lines.push(format!(
+                "{}:{} -> {}:{}: {}",
+                from.0, from.1, to.0, to.1, dist
+            ));
+        }
+    }
+
+    format!("\n{}", lines.join("\n"))
+}
+
+// show is Some(heights) to print the route, with the height map drawn around it if heights is true
+fn a_with_input(input: &str, rule: ClimbRule, from: &str, to: &str, show: Option<bool>) -> usize {
+    let grid = parse_grid(input, rule);
+    let (from, to) = (pos_param(&grid, from), pos_param(&grid, to));
 
-    let start = grid.start;
-    let best_length = best_scores[start.1 as usize][start.0 as usize];
+    let steps = grid
+        .distance(from, to)
+        .unwrap_or_else(|| panic!("No path from {:?} to {:?}", from, to));
 
-    assert!(best_length < usize::MAX);
+    if let Some(heights) = show {
+        let path = grid.paths_to(to).path_from(from).unwrap();
+        println!("{}", grid.render_path(&path, heights));
+    }
 
-    best_length
+    steps
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
+
+    let rule = climb_rule(params);
+    let show = params.get("show", false);
+    let heights = params.get("heights", false);
 
-    let val = b_with_input(&contents);
+    let val = b_with_input(&contents, rule, show.then_some(heights));
 
     val.to_string()
 }
 
-fn b_with_input(input: &str) -> usize {
-    let grid = parse_grid(input);
-    let best_scores = dijkstra(&grid);
+fn b_with_input(input: &str, rule: ClimbRule, show: Option<bool>) -> usize {
+    let grid = parse_grid(input, rule);
+
+    let (best_start, steps) = grid
+        .nearest(grid.end, |_, height| height == to_height('a'))
+        .expect("No path from any a");
+
+    if let Some(heights) = show {
+        let path = grid.paths_to(grid.end).path_from(best_start).unwrap();
+        println!("{}", grid.render_path(&path, heights));
+    }
 
-    (0..grid.height)
-        .map(|y| {
-            (0..grid.width)
-                .filter(|x| grid.height_at((*x, y)) == to_height('a'))
-                .map(|x| best_scores[y as usize][x as usize])
-                .min()
-        })
-        .filter_map(|best_in_row| best_in_row)
-        .min()
-        .unwrap()
+    steps
 }
 
-/// Given a grid, return a Vec<Vec<usize>> where out[y][x] is the length of the shortest path
-/// from (x, y) to the end point
-fn dijkstra(grid: &Grid) -> Vec<Vec<usize>> {
-    let end = grid.end;
+/// Shortest paths from every square to one target square
+struct ShortestPaths {
+    // steps[y][x] is the length of the shortest path from (x, y) to the target, or usize::MAX if
+    // there isn't one
+    steps: Vec<Vec<usize>>,
+    // next[y][x] is where to go from (x, y) to stay on a shortest path; None at the target, and
+    // anywhere the target can't be reached from
+    next: Vec<Vec<Option<(i32, i32)>>>,
+}
+
+impl ShortestPaths {
+    fn steps_from(&self, pos: (i32, i32)) -> Option<usize> {
+        let steps = self.steps[pos.1 as usize][pos.0 as usize];
+        (steps < usize::MAX).then_some(steps)
+    }
+
+    /// Every square on a shortest path from pos to the target, including both
+    fn path_from(&self, pos: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+        self.steps_from(pos)?;
 
+        let mut path = vec![pos];
+        let mut pos = pos;
+        while let Some(next) = self.next[pos.1 as usize][pos.0 as usize] {
+            path.push(next);
+            pos = next;
+        }
+
+        Some(path)
+    }
+}
+
+/// Searches backward from the target, so we get the shortest path from everywhere at once
+fn dijkstra(grid: &Grid, target: (i32, i32)) -> ShortestPaths {
     let mut best_scores = vec![vec![usize::MAX; grid.width as usize]; grid.height as usize];
+    let mut next = vec![vec![None; grid.width as usize]; grid.height as usize];
     let mut seen = vec![vec![false; grid.width as usize]; grid.height as usize];
 
+    // (path length, position, where we got there from, which is the next step forward)
     let mut to_process = VecDeque::new();
-    to_process.push_back((0, end));
+    to_process.push_back((0, target, None));
 
-    while let Some((path_length, pos)) = to_process.pop_front() {
+    while let Some((path_length, pos, came_from)) = to_process.pop_front() {
         if !seen[pos.1 as usize][pos.0 as usize] {
             best_scores[pos.1 as usize][pos.0 as usize] = path_length;
+            next[pos.1 as usize][pos.0 as usize] = came_from;
             seen[pos.1 as usize][pos.0 as usize] = true;
 
             let candidates = [
@@ -72,16 +186,19 @@ fn dijkstra(grid: &Grid) -> Vec<Vec<usize>> {
             ];
             for candidate_pos in candidates {
                 if grid.contains_pos(candidate_pos) && grid.can_move_from(candidate_pos, pos) {
-                    to_process.push_back((path_length + 1, candidate_pos));
+                    to_process.push_back((path_length + 1, candidate_pos, Some(pos)));
                 }
             }
         }
     }
 
-    best_scores
+    ShortestPaths {
+        steps: best_scores,
+        next,
+    }
 }
 
-fn parse_grid(input: &str) -> Grid {
+fn parse_grid(input: &str, climb: ClimbRule) -> Grid {
     let mut lines = input.trim().lines().peekable();
 
     let first_line = lines.peek().unwrap();
@@ -110,7 +227,7 @@ fn parse_grid(input: &str) -> Grid {
         y += 1;
     }
 
-    Grid::new(width, y, data, start.unwrap(), end.unwrap())
+    Grid::new(width, y, data, start.unwrap(), end.unwrap(), climb)
 }
 
 #[inline(always)]
@@ -118,6 +235,27 @@ fn to_height(c: char) -> i32 {
     (c as i32) - ('a' as i32)
 }
 
+/// How different the heights of neighboring squares can be, for a step from one to the other
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct ClimbRule {
+    // how much higher the next square can be
+    max_up: i32,
+    // how much lower the next square can be
+    max_down: i32,
+}
+
+impl ClimbRule {
+    /// At most one higher, and as far down as you like
+    const PUZZLE: ClimbRule = ClimbRule {
+        max_up: 1,
+        max_down: 25,
+    };
+
+    fn allows(self, height_start: i32, height_end: i32) -> bool {
+        height_end - height_start <= self.max_up && height_start - height_end <= self.max_down
+    }
+}
+
 struct Grid {
     width: i32,
     height: i32,
@@ -125,6 +263,9 @@ struct Grid {
     end: (i32, i32),
     // guaranteed to have length width * height
     data: Vec<i32>,
+    climb: ClimbRule,
+    // the search results for every target we've been asked about, so we only search once each
+    paths_cache: RefCell<HashMap<(i32, i32), Rc<ShortestPaths>>>,
 }
 
 impl Grid {
@@ -135,6 +276,7 @@ impl Grid {
         data: Vec<i32>,
         start: (i32, i32),
         end: (i32, i32),
+        climb: ClimbRule,
     ) -> Grid {
         assert!(width > 0);
         assert!(height > 0);
@@ -145,6 +287,8 @@ impl Grid {
             data,
             start,
             end,
+            climb,
+            paths_cache: RefCell::new(HashMap::new()),
         }
     }
 
@@ -158,12 +302,105 @@ impl Grid {
         let height_start = self.height_at(start_pos);
         let height_end = self.height_at(end_pos);
 
-        height_start + 1 >= height_end
+        self.climb.allows(height_start, height_end)
     }
 
     fn contains_pos(&self, pos: (i32, i32)) -> bool {
         pos.0 >= 0 && pos.0 < self.width && pos.1 >= 0 && pos.1 < self.height
     }
+
+    /// Shortest paths from everywhere to target; only searched for the first time it's asked for
+    fn paths_to(&self, target: (i32, i32)) -> Rc<ShortestPaths> {
+        if let Some(paths) = self.paths_cache.borrow().get(&target) {
+            return paths.clone();
+        }
+
+        let paths = Rc::new(dijkstra(self, target));
+        self.paths_cache.borrow_mut().insert(target, paths.clone());
+        paths
+    }
+
+    /// Length of the shortest path from one square to another, if there is one
+    fn distance(&self, from: (i32, i32), to: (i32, i32)) -> Option<usize> {
+        self.paths_to(to).steps_from(from)
+    }
+
+    /// out[i][j] is the distance from sources[i] to targets[j]; one search per target
+    fn distances(&self, sources: &[(i32, i32)], targets: &[(i32, i32)]) -> Vec<Vec<Option<usize>>> {
+        let paths: Vec<Rc<ShortestPaths>> = targets.iter().map(|t| self.paths_to(*t)).collect();
+
+        sources
+            .iter()
+            .map(|source| paths.iter().map(|p| p.steps_from(*source)).collect())
+            .collect()
+    }
+
+    /// The square closest to target (and how far it is) out of the ones matching the predicate,
+    /// which is given the position and height. Ties go to whichever comes first, reading left to
+    /// right, top to bottom.
+    fn nearest(
+        &self,
+        target: (i32, i32),
+        predicate: impl Fn((i32, i32), i32) -> bool,
+    ) -> Option<((i32, i32), usize)> {
+        let paths = self.paths_to(target);
+
+        self.positions()
+            .filter(|pos| predicate(*pos, self.height_at(*pos)))
+            .filter_map(|pos| paths.steps_from(pos).map(|steps| (pos, steps)))
+            .min_by_key(|(_, steps)| *steps)
+    }
+
+    /// How many squares (including itself) the target can be reached from
+    fn region_size(&self, target: (i32, i32)) -> usize {
+        let paths = self.paths_to(target);
+        self.positions()
+            .filter(|pos| paths.steps_from(*pos).is_some())
+            .count()
+    }
+
+    fn positions(&self) -> impl Iterator<Item = (i32, i32)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Draws the path the way the puzzle does, with an arrow on each square pointing to the next
+    /// one, and E at the end. Everything else is `.`, or its height if show_heights is set.
+    fn render_path(&self, path: &[(i32, i32)], show_heights: bool) -> String {
+        let mut chars: Vec<Vec<char>> = (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| {
+                        if show_heights {
+                            (b'a' + self.height_at((x, y)) as u8) as char
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for step in path.windows(2) {
+            let (from, to) = (step[0], step[1]);
+            chars[from.1 as usize][from.0 as usize] = match (to.0 - from.0, to.1 - from.1) {
+                (1, 0) => '>',
+                (-1, 0) => '<',
+                (0, 1) => 'v',
+                (0, -1) => '^',
+                other => panic!("Path has a jump of {:?} in it", other),
+            };
+        }
+        if let Some(last) = path.last() {
+            chars[last.1 as usize][last.0 as usize] = 'E';
+        }
+
+        let lines: Vec<String> = chars
+            .into_iter()
+            .map(|row| row.into_iter().collect())
+            .collect();
+        lines.join("\n")
+    }
 }
 
 #[cfg(test)]
@@ -179,14 +416,106 @@ abdefghi";
     #[test]
     fn sample_a() {
         let input = SAMPLE_INPUT_STR;
-        let actual = a_with_input(input);
+        let actual = a_with_input(input, ClimbRule::PUZZLE, "S", "E", None);
         assert_eq!(actual, 31);
     }
 
     #[test]
     fn sample_b() {
         let input = SAMPLE_INPUT_STR;
-        let actual = b_with_input(input);
+        let actual = b_with_input(input, ClimbRule::PUZZLE, None);
         assert_eq!(actual, 29);
     }
+
+    #[test]
+    fn routes() {
+        let grid = parse_grid(SAMPLE_INPUT_STR, ClimbRule::PUZZLE);
+        let paths = grid.paths_to(grid.end);
+
+        let path = paths.path_from(grid.start).unwrap();
+        assert_eq!(path.len(), 32);
+        assert_eq!(path.first(), Some(&grid.start));
+        assert_eq!(path.last(), Some(&grid.end));
+        for step in path.windows(2) {
+            assert!(grid.can_move_from(step[0], step[1]));
+        }
+
+        // there are a few shortest routes; this is nearly the one drawn in the puzzle text, but
+        // takes the second row's dip a bit earlier
+        assert_eq!(
+            grid.render_path(&path, false),
+            "v..v<<<<
+>v.vv<<^
+.v.v>E^^
+.>v>>>^^
+..>>>>>^"
+        );
+
+        // any tail of a shortest path is a shortest path, and the same one comes back
+        assert_eq!(paths.path_from(path[1]).unwrap()[..], path[1..]);
+        assert_eq!(paths.path_from(grid.end), Some(vec![grid.end]));
+    }
+
+    #[test]
+    fn climb_rules() {
+        // the sample route never needs to go down, so forbidding it costs nothing
+        let careful = ClimbRule {
+            max_up: 1,
+            max_down: 0,
+        };
+        assert_eq!(a_with_input(SAMPLE_INPUT_STR, careful, "S", "E", None), 31);
+
+        // big steps up allow a shortcut straight over to E
+        let bold = ClimbRule {
+            max_up: 25,
+            max_down: 25,
+        };
+        assert_eq!(a_with_input(SAMPLE_INPUT_STR, bold, "S", "E", None), 7);
+
+        let grid = parse_grid(SAMPLE_INPUT_STR, bold);
+        let path = grid.paths_to(grid.end).path_from(grid.start).unwrap();
+        assert_eq!(
+            grid.render_path(&path, true),
+            "vabqponm
+vbcryxxl
+>>>>>Exk
+acctuvwj
+abdefghi"
+        );
+    }
+
+    #[test]
+    fn queries() {
+        let grid = parse_grid(SAMPLE_INPUT_STR, ClimbRule::PUZZLE);
+
+        assert_eq!(grid.distance(grid.start, grid.end), Some(31));
+        assert_eq!(grid.distance(grid.end, grid.end), Some(0));
+        // E is a z, and the whole way down is one big drop
+        assert_eq!(grid.distance(grid.end, grid.start), Some(7));
+
+        let points = [grid.start, (0, 4), grid.end];
+        assert_eq!(
+            grid.distances(&points, &points),
+            vec![
+                vec![Some(0), Some(4), Some(31)],
+                vec![Some(4), Some(0), Some(29)],
+                vec![Some(7), Some(7), Some(0)],
+            ]
+        );
+
+        let is_a = |_, height| height == to_height('a');
+        assert_eq!(grid.nearest(grid.end, is_a), Some(((0, 4), 29)));
+        assert_eq!(
+            grid.nearest(grid.end, |pos, _| pos == grid.end),
+            Some((grid.end, 0))
+        );
+        assert_eq!(grid.nearest(grid.end, |_, height| height > 25), None);
+
+        // the sample is all one region; any square can get anywhere else, eventually
+        assert_eq!(grid.region_size(grid.start), 40);
+        assert_eq!(grid.region_size(grid.end), 40);
+
+        // every question about the same target used the same search
+        assert_eq!(grid.paths_cache.borrow().len(), 3);
+    }
 }
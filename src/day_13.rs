@@ -1,11 +1,14 @@
 use std::cmp::Ordering;
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_13.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_13.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
 
     let val = a_with_input(&contents);
 
@@ -27,8 +30,8 @@ fn a_with_input(input: &str) -> usize {
     out
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
 
     let val = b_with_input(&contents);
 
@@ -59,15 +62,11 @@ fn b_with_input(input: &str) -> usize {
 
 mod parse {
     use nom::{
-        bytes::complete::tag,
-        character::complete::digit1,
-        combinator::{eof, map},
-        multi::separated_list0,
-        sequence::tuple,
-        IResult,
+        bytes::complete::tag, combinator::map, multi::separated_list0, sequence::tuple, IResult,
     };
 
     use super::Val;
+    use crate::parsing::{blocks, parse_all, unsigned};
 
     fn parse_list(input: &str) -> IResult<&str, Val> {
         let (input, (_, vals, _)) =
@@ -77,9 +76,7 @@ mod parse {
     }
 
     fn parse_num(input: &str) -> IResult<&str, Val> {
-        let (input, val) = map(digit1, |digits: &str| digits.parse::<u32>().unwrap())(input)?;
-
-        Ok((input, Val::Num(val)))
+        map(unsigned, Val::Num)(input)
     }
 
     fn parse_val(input: &str) -> IResult<&str, Val> {
@@ -91,16 +88,14 @@ mod parse {
     }
 
     pub(super) fn parse_packet_line(line: &str) -> Val {
-        let (_, (val, _)) = tuple((parse_val, eof))(line).expect("Should parse a val");
-
-        val
+        parse_all(parse_val, line).expect("Should parse a val")
     }
 
+    // packets come in blank-line-separated pairs; this flattens them out, in order
     pub(super) fn parse_full_input(all_input: &str) -> Vec<Val> {
-        all_input
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| parse_packet_line(line))
+        blocks(all_input)
+            .flat_map(|pair| pair.lines())
+            .map(parse_packet_line)
             .collect::<Vec<Val>>()
     }
 
@@ -1,11 +1,14 @@
 use std::collections::HashSet;
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_14.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_14.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
 
     let val = a_with_input(&contents);
 
@@ -59,8 +62,8 @@ fn a_with_input(input: &str) -> usize {
     occupancy_grid.len() - start_count
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
 
     let val = b_with_input(&contents);
 
@@ -110,25 +113,9 @@ fn b_with_input(input: &str) -> usize {
 mod parse {
     use nom::character::complete::multispace0;
     use nom::multi::separated_list1;
-    use nom::{
-        bytes::complete::tag,
-        character::complete::{digit1, space1},
-        combinator::{eof, map},
-        sequence::tuple,
-        IResult,
-    };
-
-    fn parse_num(input: &str) -> IResult<&str, u32> {
-        let (input, val) = map(digit1, |digits: &str| digits.parse::<u32>().unwrap())(input)?;
-
-        Ok((input, val))
-    }
+    use nom::{bytes::complete::tag, character::complete::space1, sequence::tuple, IResult};
 
-    fn parse_pos(input: &str) -> IResult<&str, (u32, u32)> {
-        let (input, (x, _, y)) = tuple((parse_num, tag(","), parse_num))(input)?;
-
-        Ok((input, (x, y)))
-    }
+    use crate::parsing::{coord2, parse_all, unsigned};
 
     fn parse_arrow(input: &str) -> IResult<&str, ()> {
         let (input, _) = tuple((space1, tag("->"), space1))(input)?;
@@ -138,15 +125,14 @@ mod parse {
 
     fn parse_line_helper(input: &str) -> IResult<&str, Vec<(u32, u32)>> {
         let (input, _) = multispace0(input)?;
-        let (input, pos) = separated_list1(parse_arrow, parse_pos)(input)?;
+        let (input, pos) = separated_list1(parse_arrow, coord2(",", unsigned))(input)?;
         let (input, _) = multispace0(input)?;
-        let (_, _) = eof(input)?;
 
-        Ok(("", pos))
+        Ok((input, pos))
     }
 
     pub(super) fn parse_line(input: &str) -> Vec<(u32, u32)> {
-        parse_line_helper(input).unwrap().1
+        parse_all(parse_line_helper, input).unwrap()
     }
 
     #[cfg(test)]
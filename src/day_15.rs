@@ -1,13 +1,17 @@
-use std::collections::HashSet;
+use crate::interval::{Interval, IntervalSet};
+use crate::normalize::Whitespace;
+use crate::params::Params;
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_15.txt").expect("Should be able to read the file")
+fn input(params: &Params) -> String {
+    params.read_input("input/input_15.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
 
-    let val = a_with_input(&contents, 2000000);
+    let row = params.get("row", 2000000);
+
+    let val = a_with_input(&contents, row);
 
     val.to_string()
 }
@@ -16,44 +20,57 @@ fn dist(a: (i32, i32), b: (i32, i32)) -> i32 {
     (a.0 - b.0).abs() + (a.1 - b.1).abs()
 }
 
-fn a_with_input(input: &str, row: i32) -> usize {
-    let input = parse::parse_input(input);
+// The squares in the row which the sensor can see; anything in here which isn't its closest
+// beacon can't be a beacon at all
+fn sensor_coverage(sensor: (i32, i32), beacon: (i32, i32), row: i32) -> Interval {
+    // anything with distance at or under this is NOT a beacon
+    let min_dist = dist(sensor, beacon);
 
-    let mut blocked = occlusion::Occlusion::new(i32::MIN, i32::MAX);
+    let y_dist = (row - sensor.1).abs();
 
-    for (sensor_x, sensor_y, beacon_x, beacon_y) in input.iter().copied() {
-        let sensor_pos = (sensor_x, sensor_y);
-        let beacon_pos = (beacon_x, beacon_y);
-
-        // anything with distance at or under this is NOT a beacon
-        let min_dist = dist(sensor_pos, beacon_pos);
+    if y_dist > min_dist {
+        return Interval::empty();
+    }
 
-        let y_dist = (row - sensor_y).abs();
+    let rem_x_dist = min_dist - y_dist;
+    let x_min = sensor.0 - rem_x_dist;
+    let x_max = sensor.0 + rem_x_dist;
 
-        if y_dist > min_dist {
-            continue;
-        }
+    Interval::closed(x_min as i64, x_max as i64)
+}
 
-        let rem_x_dist = min_dist - y_dist;
-        let x_min = sensor_x - rem_x_dist;
-        let x_max = sensor_x + rem_x_dist;
+fn a_with_input(input: &str, row: i32) -> usize {
+    let input = parse::parse_input(input);
 
-        blocked.block_interval(x_min, x_max + 1);
-    }
+    let blocked: IntervalSet = input
+        .iter()
+        .map(|&(sx, sy, bx, by)| sensor_coverage((sx, sy), (bx, by), row))
+        .collect();
 
-    let sensors_in_row: HashSet<i32> = input
+    let in_row = |x: i32, y: i32| (y == row).then(|| Interval::closed(x as i64, x as i64));
+    let sensors: IntervalSet = input
+        .iter()
+        .filter_map(|&(x, y, _, _)| in_row(x, y))
+        .collect();
+    let beacons: IntervalSet = input
         .iter()
-        .filter(|(_, _, _, y)| *y == row)
-        .map(|(_, _, x, _)| *x)
+        .filter_map(|&(_, _, x, y)| in_row(x, y))
         .collect();
 
-    blocked.blocked_len() - sensors_in_row.len()
+    // a beacon can't be anywhere a sensor can see, or on top of a sensor (a sensor can always
+    // see its own square, so that part doesn't actually add anything); but the beacons in the
+    // row are (obviously) not places where a beacon can't be
+    let not_beacons = blocked.union(&sensors);
+
+    (not_beacons.len() - not_beacons.intersection(&beacons).len()) as usize
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
+
+    let bound: i32 = params.get("bound", 4000000);
 
-    let val = b_with_input(&contents, 0, 4000000 + 1);
+    let val = b_with_input(&contents, 0, bound + 1);
 
     val.to_string()
 }
@@ -64,10 +81,8 @@ fn b_with_input(input: &str, true_x_min: i32, true_x_max: i32) -> usize {
     assert!(true_x_min >= 0);
     assert!(true_x_max > true_x_min);
 
-    let mut occlusions = Vec::with_capacity(true_x_max as usize);
-    for _ in 0..true_x_max {
-        occlusions.push(occlusion::Occlusion::new(true_x_min, true_x_max));
-    }
+    let bounds = Interval::half_open(true_x_min as i64, true_x_max as i64);
+    let mut occlusions = vec![IntervalSet::new(); true_x_max as usize];
 
     let input = parse::parse_input(input);
 
@@ -83,18 +98,22 @@ fn b_with_input(input: &str, true_x_min: i32, true_x_max: i32) -> usize {
         let y_max = (sensor_y + base_dist).min(true_x_max - 1);
 
         for y in y_min..y_max + 1 {
-            let x_dist_max = base_dist - (sensor_y - y).abs();
-            debug_assert!(x_dist_max >= 0);
-            let x_min = sensor_x - x_dist_max;
-            let x_max = sensor_x + x_dist_max;
-
-            occlusions[y as usize].block_interval(x_min, x_max + 1);
+            // clamp to the bounds of interest, so we don't waste time + space by checking on
+            // territory we don't care about
+            occlusions[y as usize]
+                .insert(sensor_coverage(sensor_pos, beacon_pos, y).intersection(bounds));
         }
     }
 
     for y in 0..true_x_max + 1 {
-        let occ = occlusions.get(y as usize);
-        if let Some(x) = occ.unwrap().any_unblocked_space() {
+        let occ = occlusions.get(y as usize).unwrap();
+        let gaps = occ.complement(bounds);
+        if !gaps.is_empty() {
+            let x = gaps.iter().next().unwrap().start();
+            debug_assert!(
+                !occ.contains(x),
+                "No sensor should be able to see the beacon"
+            );
             return (x as usize * 4000000) + (y as usize);
         }
     }
@@ -102,158 +121,41 @@ fn b_with_input(input: &str, true_x_min: i32, true_x_max: i32) -> usize {
     unreachable!("No solution found; all rows fully blocked");
 }
 
-mod occlusion {
-    pub(super) struct Occlusion {
-        // CONTRACT: if i is a valid index, then blocks[i].0 < blocks[i].1
-        // CONTRACT: if i and i+1 are valid indices, then blocks[i].1 < blocks[i+1].0
-        // interpretation: if i is a valid index, then blocks[i] is a half-open interval [x_min, x_max)
-        //      which indicates that the (half-open) range x_min .. x_max is blocked
-        blocks: Vec<(i32, i32)>,
-        // reusable temp space. Do whatever you want with it.
-        // CONTRACT: after execution of any function, this should be empty
-        backup_data: Vec<(i32, i32)>,
-
-        // only x >= this is considered
-        true_x_min: i32,
-        // only x < this is considered
-        true_x_max: i32,
-    }
-
-    impl Occlusion {
-        pub fn new(true_x_min: i32, true_x_max: i32) -> Occlusion {
-            Self {
-                blocks: vec![],
-                backup_data: vec![],
-                true_x_min,
-                true_x_max,
-            }
-        }
-
-        pub fn block_interval(&mut self, mut x_min_new: i32, mut x_max_new: i32) {
-            assert!(x_min_new < x_max_new);
-
-            // clamp the arguments to the bounds of interest, so we don't waste time + space
-            // by checking on territory we don't care about
-            x_min_new = x_min_new.max(self.true_x_min);
-            x_max_new = x_max_new.min(self.true_x_max);
-
-            // we'll construct the new state of self.blocks into this vector, then swap them
-            self.backup_data.clear();
-
-            let mut running_interval = (x_min_new, x_max_new);
-
-            for (x_min_old, x_max_old) in self.blocks.iter().copied() {
-                let (x_min_new, x_max_new) = running_interval;
-
-                // if the old interval is completely before the running interval,
-                // then insert the old interval and keep the running interval unchanged
-                if x_max_old < x_min_new {
-                    self.backup_data.push((x_min_old, x_max_old));
-                }
-                // if the running interval is completely before the old interval, insert the
-                // running interval and make the old interval the running interval
-                else if x_max_new < x_min_old {
-                    self.backup_data.push((x_min_new, x_max_new));
-                    running_interval = (x_min_old, x_max_old);
-                }
-                // otherwise the intervals overlap; let the running interval be their union
-                else {
-                    running_interval = (x_min_old.min(x_min_new), x_max_old.max(x_max_new));
-                }
-            }
-
-            // we ALWAYS have a running interval left over
-            self.backup_data.push(running_interval);
-
-            std::mem::swap(&mut self.blocks, &mut self.backup_data);
-
-            self.backup_data.clear();
-        }
-
-        pub fn blocked_len(&self) -> usize {
-            self.blocks
-                .iter()
-                .map(|(x_min, x_max)| (x_max - x_min) as usize)
-                .sum()
-        }
-
-        pub fn any_unblocked_space(&self) -> Option<i32> {
-            let mut iter = self.blocks.iter().copied();
-
-            let first = iter.next();
-
-            if first.is_none() {
-                return Some(self.true_x_min);
-            }
-
-            let (xmin_a, xmax_a) = first.unwrap();
-
-            if self.true_x_min < xmin_a {
-                Some(self.true_x_min)
-            } else if xmax_a < self.true_x_max {
-                Some(xmax_a)
-            } else {
-                None
-            }
-        }
-    }
-}
-
 mod parse {
-    use nom::{
-        bytes::complete::tag,
-        character::complete::digit1,
-        combinator::{eof, map},
-        IResult,
-    };
-
-    fn parse_num(input: &str) -> IResult<&str, i32> {
-        if input.starts_with("-") {
-            let (input, _) = tag("-")(input)?;
-            let (input, num) = map(digit1, |digits: &str| digits.parse::<i32>().unwrap())(input)?;
-            Ok((input, -num))
-        } else {
-            let (input, val) = map(digit1, |digits: &str| digits.parse::<i32>().unwrap())(input)?;
-
-            Ok((input, val))
-        }
-    }
+    use nom::{bytes::complete::tag, IResult};
+
+    use crate::parsing::{parse_lines, signed};
 
     fn parse_line_helper(input: &str) -> IResult<&str, (i32, i32, i32, i32)> {
         let (input, _) = tag("Sensor at x=")(input)?;
-        let (input, x1) = parse_num(input)?;
+        let (input, x1) = signed(input)?;
         let (input, _) = tag(", y=")(input)?;
-        let (input, y1) = parse_num(input)?;
+        let (input, y1) = signed(input)?;
         let (input, _) = tag(": closest beacon is at x=")(input)?;
-        let (input, x2) = parse_num(input)?;
+        let (input, x2) = signed(input)?;
         let (input, _) = tag(", y=")(input)?;
-        let (input, y2) = parse_num(input)?;
-        let (_, _) = eof(input)?;
-
-        Ok(("", (x1, y1, x2, y2)))
-    }
+        let (input, y2) = signed(input)?;
 
-    fn parse_line(input: &str) -> (i32, i32, i32, i32) {
-        parse_line_helper(input).unwrap().1
+        Ok((input, (x1, y1, x2, y2)))
     }
 
     pub(super) fn parse_input(input: &str) -> Vec<(i32, i32, i32, i32)> {
-        input.lines().map(parse_line).collect()
+        parse_lines(parse_line_helper, input).unwrap()
     }
 
     #[cfg(test)]
     mod tests {
-        use super::parse_line;
+        use super::parse_input;
 
         #[test]
         fn sample_lines() {
             assert_eq!(
-                parse_line("Sensor at x=2, y=18: closest beacon is at x=-2, y=15"),
-                (2, 18, -2, 15)
+                parse_input("Sensor at x=2, y=18: closest beacon is at x=-2, y=15"),
+                vec![(2, 18, -2, 15)]
             );
             assert_eq!(
-                parse_line("Sensor at x=14, y=17: closest beacon is at x=10, y=16"),
-                (14, 17, 10, 16)
+                parse_input("Sensor at x=14, y=17: closest beacon is at x=10, y=16"),
+                vec![(14, 17, 10, 16)]
             );
         }
     }
@@ -1,14 +1,19 @@
 use opened::ValvesOpen;
 use renamer::Renamer;
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_16.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_16.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
+
+    let minutes = params.get("minutes", 30);
 
-    let val = a_with_input(&contents, 30);
+    let val = a_with_input(&contents, minutes);
 
     val.to_string()
 }
@@ -110,10 +115,12 @@ fn a_with_input(input: &str, total_time: u32) -> u32 {
     best_total_ever
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
 
-    let val = b_with_input(&contents, 26);
+    let minutes = params.get("minutes", 26);
+
+    let val = b_with_input(&contents, minutes);
 
     val.to_string()
 }
@@ -399,19 +406,11 @@ mod opened {
 
 mod parse {
     use nom::{
-        branch::alt,
-        bytes::complete::tag,
-        character::complete::{alpha1, digit1},
-        combinator::{eof, map},
-        multi::separated_list1,
+        branch::alt, bytes::complete::tag, character::complete::alpha1, multi::separated_list1,
         IResult,
     };
 
-    fn parse_num(input: &str) -> IResult<&str, u32> {
-        let (input, val) = map(digit1, |digits: &str| digits.parse::<u32>().unwrap())(input)?;
-
-        Ok((input, val))
-    }
+    use crate::parsing::{parse_lines, unsigned};
 
     fn parse_valve_name(input: &str) -> IResult<&str, String> {
         let (input, name) = alpha1(input)?;
@@ -422,23 +421,18 @@ mod parse {
         let (input, _) = tag("Valve ")(input)?;
         let (input, valve_name) = parse_valve_name(input)?;
         let (input, _) = tag(" has flow rate=")(input)?;
-        let (input, flow_rate) = parse_num(input)?;
+        let (input, flow_rate) = unsigned(input)?;
         let (input, _) = alt((
             tag("; tunnels lead to valves "),
             tag("; tunnel leads to valve "),
         ))(input)?;
-        let (input, destinations) = separated_list1(tag(", "), |s| parse_valve_name(s))(input)?;
-        let (_, _) = eof(input)?;
-
-        Ok(("", (valve_name, flow_rate, destinations)))
-    }
+        let (input, destinations) = separated_list1(tag(", "), parse_valve_name)(input)?;
 
-    fn parse_line(input: &str) -> (String, u32, Vec<String>) {
-        parse_line_helper(input).unwrap().1
+        Ok((input, (valve_name, flow_rate, destinations)))
     }
 
     pub(super) fn parse_input(input: &str) -> Vec<(String, u32, Vec<String>)> {
-        input.lines().map(parse_line).collect()
+        parse_lines(parse_line_helper, input).unwrap()
     }
 }
 
@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_17.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_17.txt", Whitespace::Insignificant)
 }
 
 fn grab_next<T: Copy>(arr: &[T], ind: &mut usize) -> T {
@@ -135,10 +138,12 @@ fn all_legal(columns: &Columns, arr: &[(usize, usize)]) -> bool {
     arr.iter().copied().all(|(x, y)| columns.is_legal(x, y))
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
+
+    let rocks = params.get("rocks", 2022);
 
-    let val = height_after_num_rocks(&contents, 2022);
+    let val = height_after_num_rocks(&contents, rocks);
 
     val.to_string()
 }
@@ -218,10 +223,12 @@ fn shift_positions(
     }
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
+
+    let rocks = params.get("rocks", 1000000000000);
 
-    let val = height_after_num_rocks(&contents, 1000000000000);
+    let val = height_after_num_rocks(&contents, rocks);
 
     val.to_string()
 }
@@ -350,6 +357,7 @@ enum RockShape {
 
 fn parse_input(input: &str) -> Vec<Dir> {
     input
+        .trim()
         .chars()
         .map(|c| match c {
             '>' => Dir::Right,
@@ -1,11 +1,14 @@
 use std::collections::{HashSet, VecDeque};
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_18.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_18.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
 
     let val = a_with_input(&contents);
 
@@ -41,8 +44,8 @@ fn a_with_input(input: &str) -> usize {
     faces_exposed
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
 
     let val = b_with_input(&contents);
 
@@ -134,52 +137,23 @@ fn b_with_input(input: &str) -> usize {
 }
 
 mod parse {
-    use nom::{
-        bytes::complete::tag,
-        character::complete::digit1,
-        combinator::{eof, map},
-        IResult,
-    };
-
-    fn parse_num(input: &str) -> IResult<&str, i32> {
-        if input.starts_with("-") {
-            let (input, _) = tag("-")(input)?;
-            let (input, num) = map(digit1, |digits: &str| digits.parse::<i32>().unwrap())(input)?;
-            Ok((input, -num))
-        } else {
-            let (input, val) = map(digit1, |digits: &str| digits.parse::<i32>().unwrap())(input)?;
-
-            Ok((input, val))
-        }
-    }
-
-    fn parse_line_helper(input: &str) -> IResult<&str, (i32, i32, i32)> {
-        let (input, x) = parse_num(input)?;
-        let (input, _) = tag(",")(input)?;
-        let (input, y) = parse_num(input)?;
-        let (input, _) = tag(",")(input)?;
-        let (input, z) = parse_num(input)?;
-        let (_, _) = eof(input)?;
-
-        Ok(("", (x, y, z)))
-    }
-
-    fn parse_line(input: &str) -> (i32, i32, i32) {
-        parse_line_helper(input).unwrap().1
-    }
+    use crate::parsing::{coord3, parse_lines, signed};
 
     pub(super) fn parse_input(input: &str) -> Vec<(i32, i32, i32)> {
-        input.lines().map(parse_line).collect()
+        parse_lines(coord3(",", signed), input).unwrap()
     }
 
     #[cfg(test)]
     mod tests {
-        use super::parse_line;
+        use super::parse_input;
 
         #[test]
         fn sample_lines() {
-            assert_eq!(parse_line("1,2,2"), (1, 2, 2));
-            assert_eq!(parse_line("3,2,115"), (3, 2, 115));
+            assert_eq!(parse_input("1,2,2"), vec![(1, 2, 2)]);
+            assert_eq!(
+                parse_input("3,2,115\n-1,0,-7"),
+                vec![(3, 2, 115), (-1, 0, -7)]
+            );
         }
     }
 }
@@ -1,11 +1,16 @@
-fn input() -> String {
-    std::fs::read_to_string("input/input_19.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_19.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
+
+    let minutes = params.get("minutes", 24);
 
-    let val = a_with_input(&contents, 24);
+    let val = a_with_input(&contents, minutes);
 
     val.to_string()
 }
@@ -25,21 +30,24 @@ fn a_with_input(input: &str, total_time: i32) -> i32 {
     total_score
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
+
+    let minutes = params.get("minutes", 32);
+    let blueprints = params.get("blueprints", 3);
 
-    let val = b_with_input(&contents);
+    let val = b_with_input(&contents, minutes, blueprints);
 
     val.to_string()
 }
 
-fn b_with_input(input: &str) -> i32 {
+fn b_with_input(input: &str, total_time: i32, num_blueprints: usize) -> i32 {
     let input = parse::parse_input(input);
 
     let best_scores: Vec<i32> = input
         .into_iter()
-        .take(3)
-        .map(|bp| optimal_geode_output(bp, 32))
+        .take(num_blueprints)
+        .map(|bp| optimal_geode_output(bp, total_time))
         .collect();
 
     let mut out = 1;
@@ -247,45 +255,28 @@ struct Blueprint {
 
 mod parse {
     use crate::day_19::Blueprint;
-    use nom::{
-        bytes::complete::tag,
-        character::complete::digit1,
-        combinator::{eof, map},
-        IResult,
-    };
-
-    fn parse_num(input: &str) -> IResult<&str, i32> {
-        if input.starts_with("-") {
-            let (input, _) = tag("-")(input)?;
-            let (input, num) = map(digit1, |digits: &str| digits.parse::<i32>().unwrap())(input)?;
-            Ok((input, -num))
-        } else {
-            let (input, val) = map(digit1, |digits: &str| digits.parse::<i32>().unwrap())(input)?;
-
-            Ok((input, val))
-        }
-    }
+    use crate::parsing::{parse_lines, unsigned};
+    use nom::{bytes::complete::tag, IResult};
 
     fn parse_line_helper(input: &str) -> IResult<&str, Blueprint> {
         let (input, _) = tag("Blueprint ")(input)?;
-        let (input, id) = parse_num(input)?;
+        let (input, id) = unsigned(input)?;
         let (input, _) = tag(": Each ore robot costs ")(input)?;
-        let (input, ore_robot_ore_cost) = parse_num(input)?;
+        let (input, ore_robot_ore_cost) = unsigned(input)?;
         let (input, _) = tag(" ore. Each clay robot costs ")(input)?;
-        let (input, clay_robot_ore_cost) = parse_num(input)?;
+        let (input, clay_robot_ore_cost) = unsigned(input)?;
         let (input, _) = tag(" ore. Each obsidian robot costs ")(input)?;
-        let (input, obsidian_robot_ore_cost) = parse_num(input)?;
+        let (input, obsidian_robot_ore_cost) = unsigned(input)?;
         let (input, _) = tag(" ore and ")(input)?;
-        let (input, obsidian_robot_clay_cost) = parse_num(input)?;
+        let (input, obsidian_robot_clay_cost) = unsigned(input)?;
         let (input, _) = tag(" clay. Each geode robot costs ")(input)?;
-        let (input, geode_robot_ore_cost) = parse_num(input)?;
+        let (input, geode_robot_ore_cost) = unsigned(input)?;
         let (input, _) = tag(" ore and ")(input)?;
-        let (input, geode_robot_obsidian_cost) = parse_num(input)?;
+        let (input, geode_robot_obsidian_cost) = unsigned(input)?;
         let (input, _) = tag(" obsidian.")(input)?;
-        let (_, _) = eof(input)?;
 
         Ok((
-            "",
+            input,
             Blueprint {
                 id,
                 ore_robot_ore_cost,
@@ -298,22 +289,18 @@ mod parse {
         ))
     }
 
-    fn parse_line(input: &str) -> Blueprint {
-        parse_line_helper(input).unwrap().1
-    }
-
     pub(super) fn parse_input(input: &str) -> Vec<Blueprint> {
-        input.lines().map(parse_line).collect()
+        parse_lines(parse_line_helper, input).unwrap()
     }
 
     #[cfg(test)]
     mod tests {
-        use super::parse_line;
+        use super::parse_input;
         use super::Blueprint;
 
         #[test]
         fn sample_lines() {
-            assert_eq!(parse_line("Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 4 ore. Each obsidian robot costs 4 ore and 14 clay. Each geode robot costs 2 ore and 16 obsidian."), Blueprint {
+            assert_eq!(parse_input("Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 4 ore. Each obsidian robot costs 4 ore and 14 clay. Each geode robot costs 2 ore and 16 obsidian.")[0], Blueprint {
                 id: 1,
                 ore_robot_ore_cost: 4,
                 clay_robot_ore_cost: 4,
@@ -1,13 +1,16 @@
 use itertools::Itertools;
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_20.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_20.txt", Whitespace::Insignificant)
 }
 
 const KEY: i64 = 811589153;
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
 
     let val = a_with_input(&contents);
 
@@ -31,8 +34,8 @@ fn a_with_input(input: &str) -> i64 {
     out
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
 
     let val = b_with_input(&contents);
 
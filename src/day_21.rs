@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_21.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_21.txt", Whitespace::Insignificant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
 
     let val = a_with_input(&contents);
 
@@ -64,8 +67,8 @@ fn a_with_input(input: &str) -> i64 {
     eval(root_id, &id_to_idx_lookup, &tokens, &mut cache)
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
 
     let val = b_with_input(&contents);
 
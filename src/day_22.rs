@@ -1,13 +1,18 @@
 use std::collections::{HashMap, HashSet};
 
-fn input() -> String {
-    std::fs::read_to_string("input/input_22.txt").expect("Should be able to read the file")
+use crate::normalize::Whitespace;
+use crate::params::Params;
+
+fn input(params: &Params) -> String {
+    params.read_input("input/input_22.txt", Whitespace::Significant)
 }
 
-pub fn a() -> String {
-    let contents = input();
+pub fn a(params: &Params) -> String {
+    let contents = input(params);
+
+    let square_width = params.get("square_width", 50);
 
-    let val = a_with_input(&contents, 50);
+    let val = a_with_input(&contents, square_width);
 
     val.to_string()
 }
@@ -83,10 +88,12 @@ fn a_with_input(input: &str, square_width: usize) -> usize {
     out
 }
 
-pub fn b() -> String {
-    let contents = input();
+pub fn b(params: &Params) -> String {
+    let contents = input(params);
+
+    let square_width = params.get("square_width", 50);
 
-    let val = b_with_input(&contents, 50);
+    let val = b_with_input(&contents, square_width);
 
     val.to_string()
 }
@@ -549,21 +556,12 @@ impl FaceEdge {
 
 mod parse {
     use nom::{
-        branch::alt,
-        bytes::complete::tag,
-        character::complete::{digit1, space0},
-        combinator::{eof, map},
-        multi::many1,
-        IResult,
+        branch::alt, bytes::complete::tag, character::complete::space0, combinator::map,
+        multi::many1, IResult,
     };
 
     use super::{Direction, Map, Row, Tile};
-
-    fn parse_num(input: &str) -> IResult<&str, usize> {
-        let (input, val) = map(digit1, |digits: &str| digits.parse::<usize>().unwrap())(input)?;
-
-        Ok((input, val))
-    }
+    use crate::parsing::{parse_all, unsigned};
 
     fn parse_tile(input: &str) -> IResult<&str, Tile> {
         alt((
@@ -576,43 +574,61 @@ mod parse {
         fn helper(input: &str) -> IResult<&str, Row> {
             let (input, x_offset) = map(space0, |spaces: &str| spaces.len())(input)?;
             let (input, tiles) = many1(parse_tile)(input)?;
-            let (_, _) = eof(input)?;
-            Ok(("", Row { x_offset, tiles }))
+            // some copies of the map are padded out to a rectangle; that's fine
+            let (input, _) = space0(input)?;
+            Ok((input, Row { x_offset, tiles }))
         }
 
-        helper(input).unwrap().1
+        parse_all(helper, input).unwrap()
+    }
+
+    // Every row in a band of square_width rows crosses the same faces, so they should all have
+    // the same offset and width. A row that lost its leading padding (e.g. the first line, if the
+    // whole input got trimmed) still has the right width, so we can put the padding back.
+    fn restore_padding(rows: &mut [Row], square_width: usize) {
+        for band in rows.chunks_mut(square_width) {
+            let shape = |row: &Row| (row.x_offset, row.tiles.len());
+
+            let (expected_offset, expected_len) = band
+                .iter()
+                .map(shape)
+                .max_by_key(|s| band.iter().filter(|row| shape(row) == *s).count())
+                .unwrap();
+
+            for row in band.iter_mut() {
+                if row.x_offset < expected_offset && row.tiles.len() == expected_len {
+                    row.x_offset = expected_offset;
+                }
+            }
+        }
     }
 
     fn parse_turn(input: &str) -> IResult<&str, Direction> {
         alt((
             map(tag("L"), |_| Direction::LTurn),
             map(tag("R"), |_| Direction::RTurn),
-            map(parse_num, |num| Direction::Forward(num)),
+            map(unsigned, Direction::Forward),
         ))(input)
     }
 
     fn parse_directions(input: &str) -> Vec<Direction> {
-        fn helper(input: &str) -> IResult<&str, Vec<Direction>> {
-            let (input, dirs) = many1(parse_turn)(input)?;
-            let (_, _) = eof(input)?;
-            Ok(("", dirs))
-        }
-
-        helper(input).unwrap().1
+        parse_all(many1(parse_turn), input).unwrap()
     }
 
     pub(super) fn parse_input(input: &str, square_width: usize) -> (Map, Vec<Direction>) {
         let mut lines = input.lines().peekable();
 
-        let rows: Vec<Row> = lines
+        let mut rows: Vec<Row> = lines
             .by_ref()
-            .take_while(|line| !line.is_empty())
-            .map(|line| parse_row(line))
+            .take_while(|line| !line.trim().is_empty())
+            .map(parse_row)
             .collect();
 
         // the above consumes the empty line without passing it to the mapper
 
-        let directions = parse_directions(lines.next().unwrap());
+        restore_padding(&mut rows, square_width);
+
+        let directions = parse_directions(lines.next().unwrap().trim());
 
         assert_eq!(lines.next(), None);
 
@@ -632,6 +648,19 @@ mod parse {
                     tiles: vec![Tile::Filled, Tile::Empty, Tile::Empty, Tile::Empty]
                 }
             );
+            assert_eq!(parse_row("        #...    "), parse_row("        #..."));
+        }
+
+        #[test]
+        fn restore_padding_test() {
+            let mut rows: Vec<Row> = ["#...", "    .#..", "    #...", "    ...."]
+                .into_iter()
+                .map(parse_row)
+                .collect();
+
+            restore_padding(&mut rows, 4);
+
+            assert!(rows.iter().all(|row| row.x_offset == 4));
         }
 
         #[test]
@@ -673,6 +702,11 @@ mod tests {
         let input = SAMPLE_INPUT_STR;
         let actual = a_with_input(input, 4);
         assert_eq!(actual, 6032);
+
+        // same again, but as if an editor had gotten its hands on it
+        let mangled = SAMPLE_INPUT_STR.trim().replace('\n', "\r\n");
+        let normalized = crate::normalize::normalize(&mangled, Whitespace::Significant);
+        assert_eq!(a_with_input(&normalized, 4), 6032);
     }
 
     #[test]
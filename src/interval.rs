@@ -0,0 +1,289 @@
+// Integer intervals and sets of them, for the days that are really about ranges: the day 4
+// section assignments, and the day 15 sensor coverage along a row.
+
+use std::fmt::{Debug, Formatter};
+
+/// A range of integers. Always stored half-open, as `[start, end)`; use `closed` when the puzzle
+/// gives both endpoints inclusively. Any interval with `end <= start` is empty, and all empty
+/// intervals are equal.
+#[derive(Copy, Clone)]
+pub struct Interval {
+    start: i64,
+    end: i64,
+}
+
+impl Interval {
+    /// The integers `start..end`
+    pub fn half_open(start: i64, end: i64) -> Interval {
+        Interval { start, end }
+    }
+
+    /// The integers `first..=last`
+    pub fn closed(first: i64, last: i64) -> Interval {
+        Interval {
+            start: first,
+            end: last + 1,
+        }
+    }
+
+    pub fn empty() -> Interval {
+        Interval { start: 0, end: 0 }
+    }
+
+    /// Smallest value in the interval (meaningless if it's empty)
+    pub fn start(self) -> i64 {
+        self.start
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.end <= self.start
+    }
+
+    pub fn len(self) -> u64 {
+        if self.is_empty() {
+            0
+        } else {
+            (self.end - self.start) as u64
+        }
+    }
+
+    pub fn contains(self, x: i64) -> bool {
+        self.start <= x && x < self.end
+    }
+
+    /// Whether every value in `other` is also in `self`; the empty interval is in everything
+    pub fn contains_interval(self, other: Interval) -> bool {
+        other.is_empty() || (self.start <= other.start && other.end <= self.end)
+    }
+
+    /// Whether there's some value in both intervals
+    pub fn overlaps(self, other: Interval) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    pub fn intersection(self, other: Interval) -> Interval {
+        Interval {
+            start: self.start.max(other.start),
+            end: self.end.min(other.end),
+        }
+    }
+}
+
+impl PartialEq for Interval {
+    fn eq(&self, other: &Interval) -> bool {
+        (self.is_empty() && other.is_empty())
+            || (self.start == other.start && self.end == other.end)
+    }
+}
+
+impl Eq for Interval {}
+
+impl Debug for Interval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            write!(f, "[)")
+        } else {
+            write!(f, "[{}, {})", self.start, self.end)
+        }
+    }
+}
+
+/// A union of intervals, kept as a sorted list of disjoint, non-touching, nonempty intervals (so
+/// `[1, 3)` and `[3, 5)` are stored as `[1, 5)`).
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct IntervalSet {
+    // CONTRACT: every interval is nonempty, and intervals[i].end < intervals[i + 1].start
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> IntervalSet {
+        IntervalSet::default()
+    }
+
+    /// Adds everything in the interval to the set, merging with whatever it touches
+    pub fn insert(&mut self, mut new: Interval) {
+        if new.is_empty() {
+            return;
+        }
+
+        // intervals[lo..hi] are the ones which overlap or touch the new one
+        let lo = self.intervals.partition_point(|old| old.end < new.start);
+        let hi = self.intervals.partition_point(|old| old.start <= new.end);
+
+        if lo < hi {
+            new.start = new.start.min(self.intervals[lo].start);
+            new.end = new.end.max(self.intervals[hi - 1].end);
+        }
+
+        self.intervals.splice(lo..hi, std::iter::once(new));
+    }
+
+    pub fn contains(&self, x: i64) -> bool {
+        let idx = self.intervals.partition_point(|iv| iv.end <= x);
+        self.intervals.get(idx).is_some_and(|iv| iv.contains(x))
+    }
+
+    /// Total number of integers in the set
+    pub fn len(&self) -> u64 {
+        self.intervals.iter().map(|iv| iv.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The maximal intervals making up the set, in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = Interval> + '_ {
+        self.intervals.iter().copied()
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut out = self.clone();
+        for iv in other.iter() {
+            out.insert(iv);
+        }
+        out
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            let both = a.intersection(b);
+            if !both.is_empty() {
+                intervals.push(both);
+            }
+
+            // whichever ends first can't meet anything else on the other side
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+
+    /// Everything in `bounds` which is not in the set
+    pub fn complement(&self, bounds: Interval) -> IntervalSet {
+        let mut intervals = Vec::new();
+        let mut next_start = bounds.start;
+
+        for iv in self.iter() {
+            let gap = Interval::half_open(next_start, iv.start).intersection(bounds);
+            if !gap.is_empty() {
+                intervals.push(gap);
+            }
+            next_start = next_start.max(iv.end);
+        }
+
+        let last = Interval::half_open(next_start, bounds.end);
+        if !last.is_empty() {
+            intervals.push(last);
+        }
+
+        IntervalSet { intervals }
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item = Interval>>(iter: T) -> IntervalSet {
+        let mut out = IntervalSet::new();
+        for iv in iter {
+            out.insert(iv);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals() {
+        let a = Interval::closed(2, 8);
+        let b = Interval::closed(3, 7);
+        let c = Interval::half_open(8, 10);
+
+        assert_eq!(a, Interval::half_open(2, 9));
+        assert_eq!(a.len(), 7);
+        assert!(a.contains(8) && !a.contains(9));
+
+        assert!(a.contains_interval(b));
+        assert!(!b.contains_interval(a));
+        assert!(b.contains_interval(Interval::empty()));
+
+        assert!(a.overlaps(c));
+        assert!(!b.overlaps(c));
+        assert_eq!(a.intersection(c), Interval::closed(8, 8));
+        assert_eq!(b.intersection(c), Interval::empty());
+        assert_eq!(Interval::half_open(5, 1).len(), 0);
+    }
+
+    #[test]
+    fn insert_merges() {
+        let mut set = IntervalSet::new();
+        set.insert(Interval::half_open(10, 12));
+        set.insert(Interval::half_open(1, 3));
+        set.insert(Interval::half_open(5, 6));
+        assert_eq!(set.iter().count(), 3);
+        assert_eq!(set.len(), 5);
+
+        // touching counts as merging
+        set.insert(Interval::half_open(3, 5));
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![Interval::half_open(1, 6), Interval::half_open(10, 12)]
+        );
+
+        // swallowing several at once
+        set.insert(Interval::closed(0, 10));
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![Interval::half_open(0, 12)]
+        );
+
+        set.insert(Interval::empty());
+        assert_eq!(set.len(), 12);
+        assert!(set.contains(0) && set.contains(11) && !set.contains(12) && !set.contains(-1));
+    }
+
+    #[test]
+    fn set_operations() {
+        let a: IntervalSet = [Interval::half_open(0, 5), Interval::half_open(10, 15)]
+            .into_iter()
+            .collect();
+        let b: IntervalSet = [Interval::half_open(3, 12), Interval::half_open(14, 20)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            a.union(&b).iter().collect::<Vec<_>>(),
+            vec![Interval::half_open(0, 20)]
+        );
+        assert_eq!(
+            a.intersection(&b).iter().collect::<Vec<_>>(),
+            vec![
+                Interval::half_open(3, 5),
+                Interval::half_open(10, 12),
+                Interval::half_open(14, 15)
+            ]
+        );
+        assert_eq!(
+            a.complement(Interval::half_open(-2, 12))
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![Interval::half_open(-2, 0), Interval::half_open(5, 10)]
+        );
+        assert!(a.complement(Interval::half_open(1, 4)).is_empty());
+        assert_eq!(
+            IntervalSet::new().complement(Interval::closed(1, 4)).len(),
+            4
+        );
+    }
+}
@@ -26,102 +26,244 @@ mod day_23;
 mod day_24;
 mod day_25;
 
+mod attempts;
+mod interval;
+mod normalize;
+mod ocr;
+mod params;
+mod parsing;
+mod submit;
+
+use attempts::{Attempt, AttemptLog, Puzzle, Verdict};
+use params::Params;
+
 fn main() -> Result<(), ()> {
-    let mut args = env::args();
-    if args.len() != 2 {
-        println!("Usage: [run command] day");
-        println!("  Example: cargo run --release -- 12b");
+    let mut args = env::args().skip(1).peekable();
+
+    if args.peek().map(|arg| arg == "record").unwrap_or(false) {
+        args.next();
+        return record_attempt(args.collect());
+    }
+
+    let submitting = args.peek().map(|arg| arg == "submit").unwrap_or(false);
+    if submitting {
+        args.next();
+    }
+
+    let day = match args.next() {
+        Some(day) => day,
+        None => {
+            print_usage();
+            return Err(());
+        }
+    };
+
+    let params = match Params::from_args(args) {
+        Ok(params) => params,
+        Err(msg) => {
+            println!("{}", msg);
+            print_usage();
+            return Err(());
+        }
+    };
+
+    // the site only wants the answer to the real puzzle, with the real input
+    if submitting && !params.is_default() {
+        println!("Did not submit: --param and --input change the puzzle");
         return Err(());
     }
 
     let start = std::time::SystemTime::now();
 
-    let answer = match args.nth(1).unwrap().as_str() {
-        "1a" => day_01::a(),
-        "1b" => day_01::b(),
+    let answer = match run_day(&day, &params) {
+        Some(answer) => answer,
+        None => {
+            println!("Unknown day variant {:?}", day);
+            return Err(());
+        }
+    };
 
-        "2a" => day_02::a(),
-        "2b" => day_02::b(),
+    let elapsed = start.elapsed().unwrap();
 
-        "3a" => day_03::a(),
-        "3b" => day_03::b(),
+    let errors = params.errors();
+    if !errors.is_empty() {
+        for error in errors {
+            println!("{}", error);
+        }
+        return Err(());
+    }
 
-        "4a" => day_04::a(),
-        "4b" => day_04::b(),
+    println!("Answer: {}", answer);
+    println!("Elapsed: {:.5} seconds", elapsed.as_secs_f32());
 
-        "5a" => day_05::a(),
-        "5b" => day_05::b(),
+    for unused in params.unused() {
+        println!("Warning: parameter {:?} is not used by {}", unused, day);
+    }
 
-        "6a" => day_06::a(),
-        "6b" => day_06::b(),
+    if submitting {
+        return submit_answer(&day, &answer);
+    }
 
-        "7a" => day_07::a(),
-        "7b" => day_07::b(),
+    warn_about_answer(&day, &answer);
 
-        "8a" => day_08::a(),
-        "8b" => day_08::b(),
+    Ok(())
+}
 
-        "9a" => day_09::a(),
-        "9b" => day_09::b(),
+// compares the answer against the attempt log; this is just advice, so problems reading the
+// log are warnings rather than errors
+fn warn_about_answer(day: &str, answer: &str) {
+    let puzzle: Puzzle = match day.parse() {
+        Ok(puzzle) => puzzle,
+        Err(_) => return,
+    };
+    let log = match AttemptLog::load(attempts::DEFAULT_LOG_PATH) {
+        Ok(log) => log,
+        Err(e) => {
+            println!("Warning: couldn't check the answer against the attempt log: {}", e);
+            return;
+        }
+    };
 
-        "10a" => day_10::a(),
-        "10b" => day_10::b(),
+    for warning in log.warnings(puzzle, answer) {
+        println!("Warning: {}", warning);
+    }
+}
 
-        "11a" => day_11::a(),
-        "11b" => day_11::b(),
+// for answers submitted by hand; args are the day, the answer, and the verdict
+fn record_attempt(args: Vec<String>) -> Result<(), ()> {
+    if args.len() != 3 {
+        print_usage();
+        return Err(());
+    }
 
-        "12a" => day_12::a(),
-        "12b" => day_12::b(),
+    let puzzle: Puzzle = args[0].parse().map_err(|e| println!("{}", e))?;
+    let verdict: Verdict = args[2].parse().map_err(|e| println!("{}", e))?;
+    let mut log = AttemptLog::load(attempts::DEFAULT_LOG_PATH).map_err(|e| println!("{}", e))?;
 
-        "13a" => day_13::a(),
-        "13b" => day_13::b(),
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
 
-        "14a" => day_14::a(),
-        "14b" => day_14::b(),
+    log.record(Attempt {
+        timestamp,
+        puzzle,
+        answer: args[1].clone(),
+        verdict,
+    })
+    .map_err(|e| println!("{}", e))?;
 
-        "15a" => day_15::a(),
-        "15b" => day_15::b(),
+    println!("Recorded {} for {}: {}", args[1], puzzle, verdict);
+    println!("Known bounds for {}: {}", puzzle, log.bounds(puzzle));
 
-        "16a" => day_16::a(),
-        "16b" => day_16::b(),
+    Ok(())
+}
 
-        "17a" => day_17::a(),
-        "17b" => day_17::b(),
+fn submit_answer(day: &str, answer: &str) -> Result<(), ()> {
+    let puzzle: Puzzle = day.parse().map_err(|e| println!("{}", e))?;
+    let config = submit::SubmitConfig::from_env().map_err(|e| println!("{}", e))?;
+    let mut log = AttemptLog::load(attempts::DEFAULT_LOG_PATH).map_err(|e| println!("{}", e))?;
 
-        "18a" => day_18::a(),
-        "18b" => day_18::b(),
+    match submit::submit(&config, &mut log, puzzle, answer) {
+        Ok(verdict) => {
+            println!("Submitted {} for {}: {}", answer, puzzle, verdict);
+            Ok(())
+        }
+        Err(msg) => {
+            println!("Did not submit: {}", msg);
+            Err(())
+        }
+    }
+}
 
-        "19a" => day_19::a(),
-        "19b" => day_19::b(),
+fn run_day(day: &str, params: &Params) -> Option<String> {
+    let answer = match day {
+        "1a" => day_01::a(params),
+        "1b" => day_01::b(params),
 
-        "20a" => day_20::a(),
-        "20b" => day_20::b(),
+        "2a" => day_02::a(params),
+        "2b" => day_02::b(params),
 
-        "21a" => day_21::a(),
-        "21b" => day_21::b(),
+        "3a" => day_03::a(params),
+        "3b" => day_03::b(params),
 
-        "22a" => day_22::a(),
-        "22b" => day_22::b(),
+        "4a" => day_04::a(params),
+        "4b" => day_04::b(params),
 
-        "23a" => day_23::a(),
-        "23b" => day_23::b(),
+        "5a" => day_05::a(params),
+        "5b" => day_05::b(params),
 
-        "24a" => day_24::a(),
-        "24b" => day_24::b(),
+        "6a" => day_06::a(params),
+        "6b" => day_06::b(params),
 
-        "25a" => day_25::a(),
-        "25b" => day_25::b(),
+        "7a" => day_07::a(params),
+        "7b" => day_07::b(params),
 
-        other => {
-            println!("Unknown day variant {:?}", other);
-            return Err(());
-        }
-    };
+        "8a" => day_08::a(params),
+        "8b" => day_08::b(params),
 
-    let elapsed = start.elapsed().unwrap();
+        "9a" => day_09::a(params),
+        "9b" => day_09::b(params),
 
-    println!("Answer: {}", answer);
-    println!("Elapsed: {:.5} seconds", elapsed.as_secs_f32());
+        "10a" => day_10::a(params),
+        "10b" => day_10::b(params),
 
-    Ok(())
+        "11a" => day_11::a(params),
+        "11b" => day_11::b(params),
+
+        "12a" => day_12::a(params),
+        "12b" => day_12::b(params),
+
+        "13a" => day_13::a(params),
+        "13b" => day_13::b(params),
+
+        "14a" => day_14::a(params),
+        "14b" => day_14::b(params),
+
+        "15a" => day_15::a(params),
+        "15b" => day_15::b(params),
+
+        "16a" => day_16::a(params),
+        "16b" => day_16::b(params),
+
+        "17a" => day_17::a(params),
+        "17b" => day_17::b(params),
+
+        "18a" => day_18::a(params),
+        "18b" => day_18::b(params),
+
+        "19a" => day_19::a(params),
+        "19b" => day_19::b(params),
+
+        "20a" => day_20::a(params),
+        "20b" => day_20::b(params),
+
+        "21a" => day_21::a(params),
+        "21b" => day_21::b(params),
+
+        "22a" => day_22::a(params),
+        "22b" => day_22::b(params),
+
+        "23a" => day_23::a(params),
+        "23b" => day_23::b(params),
+
+        "24a" => day_24::a(params),
+        "24b" => day_24::b(params),
+
+        "25a" => day_25::a(params),
+        "25b" => day_25::b(params),
+
+        _ => return None,
+    };
+
+    Some(answer)
+}
+
+fn print_usage() {
+    println!("Usage: [run command] [submit] day [--param name=value]... [--input path]");
+    println!("       [run command] record day answer verdict");
+    println!("  Example: cargo run --release -- 12b");
+    println!("  Example: cargo run --release -- 11b --param rounds=500");
+    println!("  Example: AOC_SESSION=... cargo run --release -- submit 12b");
+    println!("  Example: cargo run --release -- record 12b 4523 too_high");
 }
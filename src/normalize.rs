@@ -0,0 +1,87 @@
+// Cleans up puzzle input before the days see it, so that a file saved on Windows, or by an editor
+// with its own opinions about whitespace, parses the same as one downloaded straight from the site.
+
+/// Whether a day's input has meaningful whitespace inside its lines
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Whitespace {
+    // only the line contents matter; whitespace at either end of a line is noise
+    Insignificant,
+    // column positions matter (e.g. the day 5 drawing, the day 22 map), so leading whitespace
+    // has to be kept exactly; trailing whitespace is kept too, but days should cope without it
+    Significant,
+}
+
+/// Strips a byte order mark, converts CRLF (and lone CR) line endings to LF, drops blank lines at
+/// the start and end, and ends the result with exactly one newline. Whitespace within lines is
+/// trimmed or kept depending on the mode.
+pub fn normalize(raw: &str, whitespace: Whitespace) -> String {
+    let raw = raw.strip_prefix('\u{feff}').unwrap_or(raw);
+    let raw = raw.replace("\r\n", "\n").replace('\r', "\n");
+
+    let lines: Vec<&str> = raw
+        .split('\n')
+        .map(|line| match whitespace {
+            Whitespace::Insignificant => line.trim(),
+            Whitespace::Significant => line,
+        })
+        .collect();
+
+    let first = lines.iter().position(|line| !line.trim().is_empty());
+    let last = lines.iter().rposition(|line| !line.trim().is_empty());
+
+    let (first, last) = match (first, last) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return String::new(),
+    };
+
+    let mut out = String::with_capacity(raw.len() + 1);
+    for line in &lines[first..=last] {
+        // a blank line is a separator, whatever the mode; don't leave stray spaces on it
+        if !line.trim().is_empty() {
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_endings_and_bom() {
+        let raw = "\u{feff}1000\r\n2000\r\n\r\n3000\r\n";
+        assert_eq!(
+            normalize(raw, Whitespace::Insignificant),
+            "1000\n2000\n\n3000\n"
+        );
+        assert_eq!(
+            normalize(raw, Whitespace::Significant),
+            "1000\n2000\n\n3000\n"
+        );
+        assert_eq!(normalize("a\rb", Whitespace::Insignificant), "a\nb\n");
+    }
+
+    #[test]
+    fn insignificant_whitespace() {
+        let raw = "\n\n  $ cd /  \n$ ls\t\n   \n\n";
+        assert_eq!(normalize(raw, Whitespace::Insignificant), "$ cd /\n$ ls\n");
+    }
+
+    #[test]
+    fn significant_whitespace() {
+        let raw = "    [D]    \n[N] [C]    \n \n1\n\n";
+        assert_eq!(
+            normalize(raw, Whitespace::Significant),
+            "    [D]    \n[N] [C]    \n\n1\n"
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(normalize("", Whitespace::Insignificant), "");
+        assert_eq!(normalize(" \r\n\n", Whitespace::Significant), "");
+    }
+}
@@ -0,0 +1,257 @@
+// Reads the capital letters that some puzzles draw as pictures (e.g. the day 10 CRT), so the
+// answer comes out as text instead of something a human has to squint at.
+
+use std::fmt::{Debug, Display, Formatter};
+
+// The 4 wide, 6 tall font the puzzles use; not every letter has shown up, so not every letter
+// is here. Letters are drawn 5 columns apart, leaving a blank column between them.
+const GLYPH_WIDTH: usize = 4;
+const GLYPH_HEIGHT: usize = 6;
+const GLYPH_PITCH: usize = GLYPH_WIDTH + 1;
+
+const FONT: [(char, [&str; GLYPH_HEIGHT]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Why a picture couldn't be read
+#[derive(Eq, PartialEq)]
+pub enum OcrError {
+    /// The letters are six rows tall, so the picture has to be too (not counting blank lines
+    /// around it)
+    WrongHeight { rows: usize },
+    /// Every row has to be as wide as the first one
+    RaggedRows {
+        // 0-indexed, among the nonblank rows
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+    /// Some of the glyphs didn't match any letter in the font
+    Unrecognized {
+        /// what could be read, with `?` for each unrecognized glyph
+        partial: String,
+        /// (0-indexed position in the text, the glyph as drawn) for each unrecognized glyph
+        unrecognized: Vec<(usize, Vec<String>)>,
+    },
+}
+
+impl Display for OcrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OcrError::WrongHeight { rows } => write!(
+                f,
+                "Letters are {} rows tall, but the picture has {}",
+                GLYPH_HEIGHT, rows
+            ),
+            OcrError::RaggedRows {
+                row,
+                width,
+                expected,
+            } => write!(
+                f,
+                "Row {} of the picture is {} wide, but the first row is {} wide",
+                row + 1,
+                width,
+                expected
+            ),
+            OcrError::Unrecognized {
+                partial,
+                unrecognized,
+            } => {
+                write!(
+                    f,
+                    "Could not read {} of the letters in {:?}:",
+                    unrecognized.len(),
+                    partial
+                )?;
+                for (idx, glyph) in unrecognized.iter() {
+                    write!(f, "\n  letter {}:", idx + 1)?;
+                    for line in glyph {
+                        write!(f, "\n    {}", line)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Debug for OcrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+/// Reads a picture of `#` (lit) and `.` (unlit) pixels: six rows, all the same width. Blank lines
+/// around the picture are ignored.
+pub fn read_letters(picture: &str) -> Result<String, OcrError> {
+    let rows: Vec<Vec<bool>> = picture
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.chars().map(|c| c == '#').collect())
+        .collect();
+
+    if rows.len() != GLYPH_HEIGHT {
+        return Err(OcrError::WrongHeight { rows: rows.len() });
+    }
+
+    let width = rows[0].len();
+    if let Some((row, ragged)) = rows.iter().enumerate().find(|(_, r)| r.len() != width) {
+        return Err(OcrError::RaggedRows {
+            row,
+            width: ragged.len(),
+            expected: width,
+        });
+    }
+
+    let num_glyphs = width.div_ceil(GLYPH_PITCH);
+
+    let mut partial = String::with_capacity(num_glyphs);
+    let mut unrecognized = Vec::new();
+
+    for idx in 0..num_glyphs {
+        let glyph: Vec<String> = rows
+            .iter()
+            .map(|row| {
+                (idx * GLYPH_PITCH..idx * GLYPH_PITCH + GLYPH_WIDTH)
+                    .map(|col| match row.get(col) {
+                        Some(true) => '#',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect();
+
+        match FONT.iter().find(|(_, drawn)| drawn.iter().eq(glyph.iter())) {
+            Some((letter, _)) => partial.push(*letter),
+            None => {
+                partial.push('?');
+                unrecognized.push((idx, glyph));
+            }
+        }
+    }
+
+    if unrecognized.is_empty() {
+        Ok(partial)
+    } else {
+        Err(OcrError::Unrecognized {
+            partial,
+            unrecognized,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // draws the letters the same way the puzzles do
+    fn draw(text: &str) -> String {
+        let mut lines = vec![String::new(); GLYPH_HEIGHT];
+        for c in text.chars() {
+            let (_, glyph) = FONT.iter().find(|(letter, _)| *letter == c).unwrap();
+            for (line, glyph_line) in lines.iter_mut().zip(glyph.iter()) {
+                line.push_str(glyph_line);
+                line.push('.');
+            }
+        }
+        lines.join("\n")
+    }
+
+    #[test]
+    fn whole_font() {
+        let alphabet: String = FONT.iter().map(|(letter, _)| *letter).collect();
+        assert_eq!(read_letters(&draw(&alphabet)), Ok(alphabet));
+    }
+
+    #[test]
+    fn crt_picture() {
+        // a 40 wide picture has no blank column after the last letter, and starts with a newline
+        let picture = "
+###..#....###...##..####.###...##..#....
+#..#.#....#..#.#..#.#....#..#.#..#.#....
+#..#.#....#..#.#..#.###..###..#....#....
+###..#....###..####.#....#..#.#....#....
+#....#....#....#..#.#....#..#.#..#.#....
+#....####.#....#..#.#....###...##..####.";
+        assert_eq!(read_letters(picture), Ok("PLPAFBCL".to_string()));
+    }
+
+    #[test]
+    fn unrecognized() {
+        let picture = "
+.##..#...
+#..#.#...
+#..#.#...
+####.#...
+#..#.#...
+#..#.###.";
+        let err = read_letters(picture).unwrap_err();
+        match &err {
+            OcrError::Unrecognized {
+                partial,
+                unrecognized,
+            } => {
+                assert_eq!(partial, "A?");
+                assert_eq!(unrecognized.len(), 1);
+                assert_eq!(unrecognized[0].0, 1);
+            }
+            other => panic!("Expected an unrecognized letter, got {}", other),
+        }
+        assert_eq!(
+            err.to_string(),
+            "Could not read 1 of the letters in \"A?\":
+  letter 2:
+    #...
+    #...
+    #...
+    #...
+    #...
+    ###."
+        );
+    }
+
+    #[test]
+    fn wrong_shape() {
+        let five_rows = draw("AB").lines().take(5).collect::<Vec<_>>().join("\n");
+        let err = read_letters(&five_rows).unwrap_err();
+        assert_eq!(err, OcrError::WrongHeight { rows: 5 });
+        assert_eq!(
+            err.to_string(),
+            "Letters are 6 rows tall, but the picture has 5"
+        );
+
+        let mut ragged: Vec<String> = draw("AB").lines().map(|l| l.to_string()).collect();
+        ragged[3].truncate(7);
+        let err = read_letters(&ragged.join("\n")).unwrap_err();
+        assert_eq!(
+            err,
+            OcrError::RaggedRows {
+                row: 3,
+                width: 7,
+                expected: 10
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Row 4 of the picture is 7 wide, but the first row is 10 wide"
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::normalize::{normalize, Whitespace};
+
+/// Puzzle parameters supplied on the command line. Each solution asks for the parameters it
+/// understands (with a typed default); anything left unread is reported by the runner, so a typo
+/// like `--param round=500` doesn't silently do nothing. Likewise values which don't parse are
+/// reported by the runner (as errors, instead of an answer).
+#[derive(Debug, Default)]
+pub struct Params {
+    values: BTreeMap<String, String>,
+    input_path: Option<String>,
+    // names of the parameters the solution has asked for, so far
+    read: RefCell<BTreeSet<String>>,
+    // what was wrong with each supplied value that didn't parse, so far
+    errors: RefCell<Vec<String>>,
+}
+
+impl Params {
+    /// Parses the arguments following the day, e.g. `--param rounds=500 --input sample.txt`
+    pub fn from_args<I: Iterator<Item = String>>(mut args: I) -> Result<Params, String> {
+        let mut out = Params::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--param" => {
+                    let kv = args
+                        .next()
+                        .ok_or_else(|| "--param needs a name=value argument".to_string())?;
+                    let (name, value) = kv.split_once('=').ok_or_else(|| {
+                        format!("Expected name=value after --param, got {:?}", kv)
+                    })?;
+                    if out
+                        .values
+                        .insert(name.to_string(), value.to_string())
+                        .is_some()
+                    {
+                        return Err(format!("Parameter {:?} was given more than once", name));
+                    }
+                }
+                "--input" => {
+                    let path = args
+                        .next()
+                        .ok_or_else(|| "--input needs a path argument".to_string())?;
+                    out.input_path = Some(path);
+                }
+                other => return Err(format!("Unknown argument {:?}", other)),
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Whether anything was supplied at all; if not, the solution is answering the real puzzle
+    pub fn is_default(&self) -> bool {
+        self.values.is_empty() && self.input_path.is_none()
+    }
+
+    /// Reads and normalizes the puzzle input; this is the default path unless `--input` was given
+    pub fn read_input(&self, default_path: &str, whitespace: Whitespace) -> String {
+        let path = self.input_path.as_deref().unwrap_or(default_path);
+        let raw = std::fs::read_to_string(path).expect("Should be able to read the file");
+        normalize(&raw, whitespace)
+    }
+
+    /// Gets the named parameter, or the default if it wasn't supplied. If the supplied value
+    /// doesn't parse, this is also the default, and the problem is saved for `errors`.
+    pub fn get<T>(&self, name: &str, default: T) -> T
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.read.borrow_mut().insert(name.to_string());
+
+        match self.values.get(name) {
+            Some(raw) => raw.parse::<T>().unwrap_or_else(|e| {
+                self.bad_value(name, raw, e);
+                default
+            }),
+            None => default,
+        }
+    }
+
+    /// Gets the named comma-separated list parameter, or the default if it wasn't supplied (or
+    /// any item in it doesn't parse, in which case the problem is saved for `errors`)
+    pub fn get_list<T>(&self, name: &str, default: &[T]) -> Vec<T>
+    where
+        T: FromStr + Clone,
+        T::Err: Display,
+    {
+        self.read.borrow_mut().insert(name.to_string());
+
+        match self.values.get(name) {
+            Some(raw) => raw
+                .split(',')
+                .map(|tok| tok.trim().parse::<T>())
+                .collect::<Result<Vec<T>, _>>()
+                .unwrap_or_else(|e| {
+                    self.bad_value(name, raw, e);
+                    default.to_vec()
+                }),
+            None => default.to_vec(),
+        }
+    }
+
+    fn bad_value<E: Display>(&self, name: &str, raw: &str, e: E) {
+        self.errors
+            .borrow_mut()
+            .push(format!("Bad value {:?} for parameter {}: {}", raw, name, e));
+    }
+
+    /// Everything wrong with the supplied values the solution asked for; if there's anything
+    /// here, the solution ran with (some) defaults instead, so its answer means nothing
+    pub fn errors(&self) -> Vec<String> {
+        self.errors.borrow().clone()
+    }
+
+    /// Names of supplied parameters the solution never asked for
+    pub fn unused(&self) -> Vec<&str> {
+        let read = self.read.borrow();
+        self.values
+            .keys()
+            .filter(|name| !read.contains(name.as_str()))
+            .map(|name| name.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(raw: &[&str]) -> impl Iterator<Item = String> {
+        raw.iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn defaults_and_overrides() {
+        let params = Params::from_args(args(&["--param", "rounds=500"])).unwrap();
+
+        assert_eq!(params.get("rounds", 20_usize), 500);
+        assert_eq!(params.get("minutes", 30_u32), 30);
+        assert!(params.unused().is_empty());
+        assert!(!params.is_default());
+
+        assert!(Params::from_args(args(&[])).unwrap().is_default());
+        assert!(!Params::from_args(args(&["--input", "x.txt"]))
+            .unwrap()
+            .is_default());
+    }
+
+    #[test]
+    fn lists() {
+        let params = Params::from_args(args(&["--param", "milestones=1, 2,3"])).unwrap();
+
+        assert_eq!(params.get_list("milestones", &[20_usize]), vec![1, 2, 3]);
+        assert_eq!(params.get_list("other", &[20_usize]), vec![20]);
+    }
+
+    #[test]
+    fn unused_params() {
+        let params =
+            Params::from_args(args(&["--param", "round=5", "--param", "rounds=6"])).unwrap();
+
+        assert_eq!(params.get("rounds", 20_usize), 6);
+        assert_eq!(params.unused(), vec!["round"]);
+    }
+
+    #[test]
+    fn bad_values() {
+        let params = Params::from_args(args(&[
+            "--param",
+            "rounds=abc",
+            "--param",
+            "milestones=1,x",
+            "--param",
+            "show=true",
+        ]))
+        .unwrap();
+
+        assert_eq!(params.get("rounds", 20_usize), 20);
+        assert_eq!(params.get_list("milestones", &[20_usize]), vec![20]);
+        assert!(params.get("show", false));
+        assert_eq!(
+            params.errors(),
+            vec![
+                "Bad value \"abc\" for parameter rounds: invalid digit found in string",
+                "Bad value \"1,x\" for parameter milestones: invalid digit found in string",
+            ]
+        );
+        assert!(params.unused().is_empty());
+    }
+
+    #[test]
+    fn bad_args() {
+        assert!(Params::from_args(args(&["--param"])).is_err());
+        assert!(Params::from_args(args(&["--param", "rounds"])).is_err());
+        assert!(Params::from_args(args(&["--param", "a=1", "--param", "a=2"])).is_err());
+        assert!(Params::from_args(args(&["--verbose"])).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+// Nom helpers for the syntax that shows up over and over in the puzzle inputs: numbers,
+// comma-separated coordinates, one-thing-per-line inputs, and blank-line-separated blocks.
+
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+
+use nom::{
+    bytes::complete::tag,
+    character::complete::{char, digit1},
+    combinator::{all_consuming, map_res, opt, recognize},
+    sequence::{pair, tuple},
+    IResult,
+};
+
+/// Parses a run of digits into any unsigned (or signed, if you know it's nonnegative) integer type
+pub fn unsigned<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(digit1, |digits: &str| digits.parse::<T>())(input)
+}
+
+/// Parses an integer with an optional leading minus sign
+pub fn signed<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(recognize(pair(opt(char('-')), digit1)), |digits: &str| {
+        digits.parse::<T>()
+    })(input)
+}
+
+/// Parses two items with a separator between them, e.g. `498,4`
+pub fn coord2<'a, O, F>(
+    sep: &'static str,
+    item: F,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (O, O)>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O> + Copy,
+{
+    move |input: &'a str| {
+        let (input, (x, _, y)) = tuple((item, tag(sep), item))(input)?;
+        Ok((input, (x, y)))
+    }
+}
+
+/// Parses three items with a separator between each, e.g. `1,2,-5`
+pub fn coord3<'a, O, F>(
+    sep: &'static str,
+    item: F,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (O, O, O)>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O> + Copy,
+{
+    move |input: &'a str| {
+        let (input, (x, _, y, _, z)) = tuple((item, tag(sep), item, tag(sep), item))(input)?;
+        Ok((input, (x, y, z)))
+    }
+}
+
+/// Splits the input into chunks separated by one or more blank lines. Each chunk is a slice of
+/// the original input, without its trailing newline.
+pub fn blocks(input: &str) -> impl Iterator<Item = &str> {
+    let mut rest = input;
+
+    std::iter::from_fn(move || {
+        // skip any blank lines before the block
+        loop {
+            let line_end = rest.find('\n').map(|i| i + 1).unwrap_or(rest.len());
+            if line_end == 0 || !rest[..line_end].trim().is_empty() {
+                break;
+            }
+            rest = &rest[line_end..];
+        }
+
+        if rest.is_empty() {
+            return None;
+        }
+
+        // the block runs until the next blank line, or the end of the input
+        let mut block_end = 0;
+        while block_end < rest.len() {
+            let line_end = rest[block_end..]
+                .find('\n')
+                .map(|i| block_end + i + 1)
+                .unwrap_or(rest.len());
+            if rest[block_end..line_end].trim().is_empty() {
+                break;
+            }
+            block_end = line_end;
+        }
+
+        let block = rest[..block_end].trim_end_matches(['\n', '\r']);
+        rest = &rest[block_end..];
+        Some(block)
+    })
+}
+
+/// Runs the parser over the whole input, which must be completely consumed
+pub fn parse_all<'a, O, F>(parser: F, input: &'a str) -> Result<O, ParseError>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    match all_consuming(parser)(input) {
+        Ok((_, out)) => Ok(out),
+        Err(e) => Err(ParseError::from_nom(input, e)),
+    }
+}
+
+/// Runs the parser over each line of the input; every line must be completely consumed
+pub fn parse_lines<'a, O, F>(mut parser: F, input: &'a str) -> Result<Vec<O>, ParseError>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(line_idx, line)| {
+            parse_all(&mut parser, line).map_err(|mut e| {
+                e.line += line_idx;
+                e
+            })
+        })
+        .collect()
+}
+
+/// Where (and roughly why) a parse failed. The Debug output is the same as the Display output,
+/// so that `.unwrap()` on a failed parse still gives a readable panic message.
+#[derive(Eq, PartialEq)]
+pub struct ParseError {
+    // 1-indexed, like an editor
+    pub line: usize,
+    // 1-indexed, in characters
+    pub column: usize,
+    // the full text of the offending line
+    pub line_text: String,
+    pub kind: String,
+}
+
+impl ParseError {
+    fn from_nom(input: &str, e: nom::Err<nom::error::Error<&str>>) -> ParseError {
+        let (remaining, kind) = match e {
+            nom::Err::Error(e) | nom::Err::Failure(e) => (e.input, format!("{:?}", e.code)),
+            nom::Err::Incomplete(_) => ("", "incomplete input".to_string()),
+        };
+
+        // nom hands back the unparsed suffix, so the failure point is wherever that starts
+        let offset = input.len() - remaining.len();
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = input[offset..]
+            .find('\n')
+            .map(|i| offset + i)
+            .unwrap_or(input.len());
+
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            line_text: input[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_string(),
+            kind,
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Parse error ({}) at line {}, column {}:",
+            self.kind, self.line, self.column
+        )?;
+        writeln!(f, "    {}", self.line_text)?;
+        write!(f, "    {}^", " ".repeat(self.column - 1))
+    }
+}
+
+impl Debug for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(unsigned::<u32>("123,4"), Ok((",4", 123)));
+        assert!(unsigned::<u32>("-123").is_err());
+        assert_eq!(signed::<i32>("-123,4"), Ok((",4", -123)));
+        assert_eq!(signed::<i64>("77"), Ok(("", 77)));
+        // too big for the type is an error, not a panic
+        assert!(unsigned::<u8>("300").is_err());
+    }
+
+    #[test]
+    fn coords() {
+        assert_eq!(
+            coord2(",", unsigned::<u32>)("498,4 ->"),
+            Ok((" ->", (498, 4)))
+        );
+        assert_eq!(coord3(",", signed::<i32>)("1,-2,3"), Ok(("", (1, -2, 3))));
+        assert!(coord3(",", signed::<i32>)("1,-2").is_err());
+    }
+
+    #[test]
+    fn split_blocks() {
+        let input = "1000\n2000\n\n4000\n\n\n5000\n6000\n";
+        assert_eq!(
+            blocks(input).collect::<Vec<_>>(),
+            vec!["1000\n2000", "4000", "5000\n6000"]
+        );
+
+        assert_eq!(blocks("\n\na\n").collect::<Vec<_>>(), vec!["a"]);
+        assert_eq!(blocks("").count(), 0);
+        // leading whitespace inside a block is kept
+        assert_eq!(
+            blocks("    [D]\n[N] [C]\n\nmove 1").collect::<Vec<_>>(),
+            vec!["    [D]\n[N] [C]", "move 1"]
+        );
+    }
+
+    #[test]
+    fn whole_input() {
+        assert_eq!(
+            parse_lines(coord2(",", signed::<i32>), "1,2\n-3,4\n"),
+            Ok(vec![(1, 2), (-3, 4)])
+        );
+
+        let err = parse_lines(coord2(",", signed::<i32>), "1,2\n-3;4\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert_eq!(err.line_text, "-3;4");
+        assert_eq!(
+            err.to_string(),
+            "Parse error (Tag) at line 2, column 3:\n    -3;4\n      ^"
+        );
+
+        // trailing junk is an error too
+        let err = parse_all(unsigned::<u32>, "12\n34").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 3));
+    }
+}
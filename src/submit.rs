@@ -0,0 +1,429 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::attempts::{Attempt, AttemptLog, Puzzle, Verdict, REJECTION_COOLDOWN};
+
+const DEFAULT_ENDPOINT: &str = "https://adventofcode.com/2022";
+const USER_AGENT: &str = "github.com/rodya-mirov/aoc_2022";
+
+/// Where to send answers, and who to send them as
+pub struct SubmitConfig {
+    // answers are posted to {endpoint}/day/{day}/answer
+    pub endpoint: String,
+    pub session: String,
+}
+
+impl SubmitConfig {
+    /// Reads the config from the environment: AOC_SESSION (required) is the session cookie,
+    /// and AOC_ENDPOINT (optional) overrides the site, e.g. to point at a mock server
+    pub fn from_env() -> Result<SubmitConfig, String> {
+        let session = std::env::var("AOC_SESSION")
+            .map_err(|_| "Set AOC_SESSION to your session cookie to submit".to_string())?;
+        let endpoint =
+            std::env::var("AOC_ENDPOINT").unwrap_or_else(|_| DEFAULT_ENDPOINT.to_string());
+
+        Ok(SubmitConfig { endpoint, session })
+    }
+}
+
+/// Submits the answer, unless the log says there's no point, and records what the site said.
+/// Returns the verdict, or an explanation of why nothing was sent.
+pub fn submit(
+    config: &SubmitConfig,
+    log: &mut AttemptLog,
+    puzzle: Puzzle,
+    answer: &str,
+) -> Result<Verdict, String> {
+    submit_at(config, log, puzzle, answer, now_secs())
+}
+
+// submit, as if it's currently `now` (in unix seconds)
+fn submit_at(
+    config: &SubmitConfig,
+    log: &mut AttemptLog,
+    puzzle: Puzzle,
+    answer: &str,
+    now: u64,
+) -> Result<Verdict, String> {
+    // checked up front, so we don't send something we couldn't record
+    Attempt::check_answer(answer)?;
+
+    if let Some(correct) = log.correct_answer(puzzle) {
+        return Err(format!(
+            "Already solved {}; the answer was {}",
+            puzzle, correct
+        ));
+    }
+
+    if let Some(rejected) = log.rejection_of(puzzle, answer) {
+        return Err(format!(
+            "Not resubmitting {} for {}; it was already rejected ({})",
+            answer, puzzle, rejected.verdict
+        ));
+    }
+
+    let bounds = log.bounds(puzzle);
+    if !bounds.allows(answer) {
+        return Err(format!(
+            "Not submitting {} for {}; it's outside the known bounds {}",
+            answer, puzzle, bounds
+        ));
+    }
+
+    if let Some(not_before) = log.not_before(puzzle) {
+        if now < not_before {
+            return Err(format!(
+                "Rate limited; wait {} more seconds before submitting {}",
+                not_before - now,
+                puzzle
+            ));
+        }
+    }
+
+    let body = post_answer(config, puzzle, answer)?;
+    let verdict = parse_response(&body)?;
+
+    log.record(Attempt {
+        timestamp: now,
+        puzzle,
+        answer: answer.to_string(),
+        verdict,
+    })?;
+
+    // the log assumes the usual cooldown after a wrong answer, so only a longer one needs noting
+    if verdict.is_rejection() {
+        if let Some(wait) = parse_rejection_wait(&body).filter(|&w| w > REJECTION_COOLDOWN) {
+            log.record(Attempt {
+                timestamp: now,
+                puzzle,
+                answer: answer.to_string(),
+                verdict: Verdict::Wait(wait),
+            })?;
+        }
+    }
+
+    Ok(verdict)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Clock should be after 1970")
+        .as_secs()
+}
+
+// curl does the HTTPS for us, so we don't need a TLS stack just for this
+fn post_answer(config: &SubmitConfig, puzzle: Puzzle, answer: &str) -> Result<String, String> {
+    let url = format!(
+        "{}/day/{}/answer",
+        config.endpoint.trim_end_matches('/'),
+        puzzle.day
+    );
+
+    // the session cookie goes in through stdin (as a curl config file), since anyone on the
+    // machine can see the command line
+    let mut child = Command::new("curl")
+        .args(["--config", "-"])
+        .arg("--silent")
+        .arg("--show-error")
+        .args(["--user-agent", USER_AGENT])
+        .args(["--data-urlencode", &format!("level={}", puzzle.level)])
+        .args(["--data-urlencode", &format!("answer={}", answer)])
+        .arg(&url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run curl: {}", e))?;
+
+    // curl reads the config until stdin closes, so close it before waiting
+    let mut stdin = child.stdin.take().unwrap();
+    writeln!(
+        stdin,
+        "cookie = \"session={}\"",
+        quote_config(&config.session)
+    )
+    .map_err(|e| format!("Could not pass the session to curl: {}", e))?;
+    drop(stdin);
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Could not run curl: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Posting to {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// escapes a value to go inside double quotes in a curl config file
+fn quote_config(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Works out the verdict from the HTML the site sends back
+fn parse_response(body: &str) -> Result<Verdict, String> {
+    if body.contains("That's the right answer") {
+        Ok(Verdict::Correct)
+    } else if body.contains("your answer is too high") {
+        Ok(Verdict::TooHigh)
+    } else if body.contains("your answer is too low") {
+        Ok(Verdict::TooLow)
+    } else if body.contains("That's not the right answer") {
+        Ok(Verdict::Wrong)
+    } else if body.contains("You don't seem to be solving the right level") {
+        Ok(Verdict::WrongLevel)
+    } else if body.contains("You gave an answer too recently") {
+        parse_wait(body)
+            .map(Verdict::Wait)
+            .ok_or_else(|| "Rate limited, but couldn't find the wait time".to_string())
+    } else {
+        Err(format!("Didn't understand the response:\n{}", body))
+    }
+}
+
+// finds the "You have 1m 23s left to wait" part; either unit can be missing
+fn parse_wait(body: &str) -> Option<u64> {
+    let end = body.find(" left to wait")?;
+    let start = body[..end].rfind("You have ")? + "You have ".len();
+
+    let mut total = 0;
+    for token in body[start..end].split_whitespace() {
+        if let Some(mins) = token.strip_suffix('m') {
+            total += mins.parse::<u64>().ok()? * 60;
+        } else if let Some(secs) = token.strip_suffix('s') {
+            total += secs.parse::<u64>().ok()?;
+        } else {
+            return None;
+        }
+    }
+
+    Some(total)
+}
+
+// finds the "please wait 5 minutes before trying again" part of a wrong answer response
+fn parse_rejection_wait(body: &str) -> Option<u64> {
+    let start = body.find("please wait ")? + "please wait ".len();
+    let mut tokens = body[start..].split_whitespace();
+
+    let amount = match tokens.next()? {
+        "one" | "a" => 1,
+        num => num.parse::<u64>().ok()?,
+    };
+
+    let unit = tokens.next()?;
+    if unit.starts_with("minute") {
+        Some(amount * 60)
+    } else if unit.starts_with("second") {
+        Some(amount)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    use super::*;
+
+    const RIGHT: &str = "<article><p>That's the right answer!  You are <em>one gold star</em> closer to collecting enough star fruit.</p></article>";
+    const TOO_HIGH: &str = "<article><p>That's not the right answer; your answer is too high.  If you're stuck, make sure you're using the full input data; please wait one minute before trying again.</p></article>";
+    const TOO_LOW_AGAIN: &str = "<article><p>That's not the right answer; your answer is too low.  Because you have guessed incorrectly 6 times on this puzzle, please wait 5 minutes before trying again.</p></article>";
+    const TOO_RECENT: &str = "<article><p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 12s left to wait. <a href=\"/2022/day/1\">[Return to Day 1]</a></p></article>";
+
+    #[test]
+    fn parse_responses() {
+        assert_eq!(parse_response(RIGHT), Ok(Verdict::Correct));
+        assert_eq!(parse_response(TOO_HIGH), Ok(Verdict::TooHigh));
+        assert_eq!(parse_response(TOO_RECENT), Ok(Verdict::Wait(72)));
+        assert_eq!(
+            parse_response("That's not the right answer; your answer is too low."),
+            Ok(Verdict::TooLow)
+        );
+        assert_eq!(
+            parse_response("That's not the right answer.  If you're stuck"),
+            Ok(Verdict::Wrong)
+        );
+        assert_eq!(
+            parse_response(
+                "You don't seem to be solving the right level.  Did you already complete it?"
+            ),
+            Ok(Verdict::WrongLevel)
+        );
+        assert!(parse_response("<html>Internal Server Error</html>").is_err());
+
+        assert_eq!(parse_rejection_wait(TOO_HIGH), Some(60));
+        assert_eq!(parse_rejection_wait(TOO_LOW_AGAIN), Some(300));
+        assert_eq!(parse_rejection_wait(RIGHT), None);
+
+        assert_eq!(parse_wait("You have 9s left to wait."), Some(9));
+        assert_eq!(parse_wait("You have 2m left to wait."), Some(120));
+    }
+
+    // serves one canned response per connection, and hands back the raw requests it saw
+    fn mock_server(responses: Vec<&'static str>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}/2022", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for body in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(len) = line.to_lowercase().strip_prefix("content-length:") {
+                        content_length = len.trim().parse::<usize>().unwrap();
+                    }
+                    request.push_str(&line);
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut form = vec![0; content_length];
+                reader.read_exact(&mut form).unwrap();
+                request.push_str(&String::from_utf8(form).unwrap());
+                requests.push(request);
+
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+            requests
+        });
+
+        (endpoint, handle)
+    }
+
+    #[test]
+    fn submit_against_mock_server() {
+        let (endpoint, server) = mock_server(vec![TOO_HIGH, RIGHT]);
+        let config = SubmitConfig {
+            endpoint,
+            session: "abc123".to_string(),
+        };
+
+        let path = std::env::temp_dir()
+            .join(format!("aoc_2022_submit_{}", std::process::id()))
+            .join("attempts.txt");
+        let _ = std::fs::remove_file(&path);
+        let mut log = AttemptLog::load(&path).unwrap();
+
+        let puzzle = Puzzle { day: 5, level: 2 };
+
+        assert_eq!(
+            submit_at(&config, &mut log, puzzle, "900", 1000),
+            Ok(Verdict::TooHigh)
+        );
+        // known-wrong answers never reach the server
+        assert_eq!(
+            submit_at(&config, &mut log, puzzle, "900", 1010),
+            Err("Not resubmitting 900 for 5b; it was already rejected (too_high)".to_string())
+        );
+        // or answers already ruled out by a too-high verdict
+        assert_eq!(
+            submit_at(&config, &mut log, puzzle, "901", 1010),
+            Err("Not submitting 901 for 5b; it's outside the known bounds (-inf, 900)".to_string())
+        );
+        // or anything that couldn't go in the log
+        let err = submit_at(&config, &mut log, puzzle, "\n9\n", 1010).unwrap_err();
+        assert!(err.starts_with("Can't use"), "{}", err);
+        // or anything during the cooldown after a wrong answer
+        assert_eq!(
+            submit_at(&config, &mut log, puzzle, "800", 1010),
+            Err("Rate limited; wait 50 more seconds before submitting 5b".to_string())
+        );
+
+        assert_eq!(
+            submit_at(&config, &mut log, puzzle, "800", 1060),
+            Ok(Verdict::Correct)
+        );
+        // and nothing gets sent after it's solved
+        assert_eq!(
+            submit_at(&config, &mut log, puzzle, "700", 2000),
+            Err("Already solved 5b; the answer was 800".to_string())
+        );
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("POST /2022/day/5/answer "));
+        assert!(requests[0].contains("Cookie: session=abc123"));
+        assert!(requests[0].ends_with("level=2&answer=900"));
+        assert!(requests[1].ends_with("level=2&answer=800"));
+
+        let reloaded = AttemptLog::load(&path).unwrap();
+        assert_eq!(reloaded.correct_answer(puzzle), Some("800"));
+    }
+
+    #[test]
+    fn longer_cooldown() {
+        let (endpoint, server) = mock_server(vec![TOO_LOW_AGAIN]);
+        let config = SubmitConfig {
+            endpoint,
+            session: "abc123".to_string(),
+        };
+
+        let path = std::env::temp_dir()
+            .join(format!("aoc_2022_submit_{}", std::process::id()))
+            .join("longer_cooldown.txt");
+        let _ = std::fs::remove_file(&path);
+        let mut log = AttemptLog::load(&path).unwrap();
+
+        let puzzle = Puzzle { day: 2, level: 1 };
+
+        assert_eq!(
+            submit_at(&config, &mut log, puzzle, "10", 1000),
+            Ok(Verdict::TooLow)
+        );
+        assert_eq!(log.not_before(puzzle), Some(1300));
+        assert!(log.rejection_of(puzzle, "10").is_some());
+
+        let err = submit_at(&config, &mut log, puzzle, "20", 1100).unwrap_err();
+        assert!(err.starts_with("Rate limited; wait 200 more"), "{}", err);
+
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn respects_rate_limit() {
+        let (endpoint, server) = mock_server(vec![TOO_RECENT]);
+        let config = SubmitConfig {
+            endpoint,
+            session: "abc123".to_string(),
+        };
+
+        let path = std::env::temp_dir()
+            .join(format!("aoc_2022_submit_{}", std::process::id()))
+            .join("rate_limit.txt");
+        let _ = std::fs::remove_file(&path);
+        let mut log = AttemptLog::load(&path).unwrap();
+
+        let puzzle = Puzzle { day: 1, level: 1 };
+
+        assert_eq!(
+            submit(&config, &mut log, puzzle, "1"),
+            Ok(Verdict::Wait(72))
+        );
+        let err = submit(&config, &mut log, puzzle, "1").unwrap_err();
+        assert!(err.starts_with("Rate limited"), "{}", err);
+
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+}